@@ -0,0 +1,655 @@
+//! Compact adjacency list used by the sorting algorithms.
+//!
+//! `Graph` stores plain edges, which is the natural input format but forces every algorithm step to scan all of them.
+//! `Adjacency` is built once per sort and maps nodes to dense indices `0..n`, so per-node data can live in plain
+//! `Vec`s and the successors of a node are a contiguous slice (compressed sparse row layout).
+
+use core::{cmp::Ordering, hash::Hash, ops::Range};
+
+use heapless::{binary_heap::Min, BinaryHeap, Vec};
+
+use crate::{node_index::NodeIndex, Edge, Error};
+
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub(crate) struct Adjacency<N, const EDGES: usize, const NODES: usize> {
+    /// node id <-> dense index
+    nodes: NodeIndex<N, NODES>,
+    /// the successors of node `i` are `targets[starts[i]..starts[i + 1]]` (or until the end for the last node)
+    starts: Vec<usize, NODES>,
+    targets: Vec<usize, EDGES>,
+    /// number of incoming edges per node. Parallel edges count separately and are all removed together with their
+    /// source, so duplicates can't make a node ready early
+    in_degrees: Vec<usize, NODES>,
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Adjacency<N, EDGES, NODES> {
+    /// Build the adjacency list from edges plus explicitly added (possibly isolated) nodes.
+    /// Dense indices are assigned in order of first appearance: `nodes` first, then edge endpoints.
+    pub(crate) fn new(nodes: &[N], edges: &[Edge<N>]) -> Result<Self, Error<N, EDGES>> {
+        let mut adjacency = Self::empty();
+        adjacency.rebuild(nodes, edges)?;
+        Ok(adjacency)
+    }
+
+    /// An adjacency list without nodes, to be filled by [`Adjacency::rebuild`].
+    pub(crate) const fn empty() -> Self {
+        Self {
+            nodes: NodeIndex::new(),
+            starts: Vec::new(),
+            targets: Vec::new(),
+            in_degrees: Vec::new(),
+        }
+    }
+
+    /// Like [`Adjacency::new`], but reuses self, e.g. to keep it in a [`crate::Scratch`].
+    pub(crate) fn rebuild(
+        &mut self,
+        nodes: &[N],
+        edges: &[Edge<N>],
+    ) -> Result<(), Error<N, EDGES>> {
+        self.nodes.clear();
+        let endpoints = edges.iter().flat_map(|edge| [edge.from, edge.to]);
+        for node in nodes.iter().copied().chain(endpoints) {
+            if self.nodes.insert(node).is_none() {
+                return Err(Error::nodes_over_capacity(nodes, edges));
+            }
+        }
+
+        // can't fail: both have capacity NODES
+        let len = self.nodes.len();
+        self.starts.clear();
+        let _ = self.starts.resize(len, 0);
+        self.in_degrees.clear();
+        let _ = self.in_degrees.resize(len, 0);
+        let needed = edges.len();
+        self.targets.clear();
+        // can't fail for a `Graph`: there are exactly `edges.len()` <= EDGES targets
+        self.targets
+            .resize(needed, 0)
+            .map_err(|_| Error::OverCapacity { needed })?;
+        let Self {
+            nodes,
+            starts,
+            targets,
+            in_degrees,
+        } = self;
+        // unwrap safety: all endpoints have been indexed above
+        let index = |node| nodes.index_of(node).unwrap();
+        let pairs = edges.iter().map(|edge| (index(edge.from), index(edge.to)));
+        fill_csr(pairs, starts, targets, in_degrees);
+        Ok(())
+    }
+
+    /// Dense index of `node`, if it is part of the graph.
+    pub(crate) fn index(&self, node: N) -> Option<usize> {
+        self.nodes.index_of(node)
+    }
+
+    /// Node id of dense index `idx`.
+    pub(crate) fn node(&self, idx: usize) -> N {
+        self.nodes[idx]
+    }
+
+    /// Node ids by dense index.
+    pub(crate) fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// Keep only the node index, e.g. for a [`crate::NodeMap`].
+    pub(crate) fn into_nodes(self) -> NodeIndex<N, NODES> {
+        self.nodes
+    }
+
+    /// Number of distinct nodes.
+    pub(crate) fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Dense indices of all nodes without incoming edges, in index order.
+    pub(crate) fn starting_nodes(&self) -> Vec<usize, NODES> {
+        self.in_degrees
+            .iter()
+            .enumerate()
+            .filter(|(_, &in_degree)| in_degree == 0)
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Positions of the successors of `idx` in `targets`.
+    fn successor_range(&self, idx: usize) -> Range<usize> {
+        let end = self
+            .starts
+            .get(idx + 1)
+            .copied()
+            .unwrap_or(self.targets.len());
+        self.starts[idx]..end
+    }
+
+    /// Dense indices of the successors of `idx`, one per edge, in edge order.
+    pub(crate) fn successors(&self, idx: usize) -> &[usize] {
+        &self.targets[self.successor_range(idx)]
+    }
+
+    /// Remove the outgoing edges of `idx`, calling `on_starting` for every successor that has no incoming
+    /// edges left.
+    pub(crate) fn remove_outgoing(&mut self, idx: usize, mut on_starting: impl FnMut(usize)) {
+        for pos in self.successor_range(idx) {
+            let succ = self.targets[pos];
+            self.in_degrees[succ] -= 1;
+            if self.in_degrees[succ] == 0 {
+                on_starting(succ);
+            }
+        }
+    }
+
+    /// Undo [`Adjacency::remove_outgoing`] for `idx`.
+    fn restore_outgoing(&mut self, idx: usize) {
+        for pos in self.successor_range(idx) {
+            self.in_degrees[self.targets[pos]] += 1;
+        }
+    }
+
+    /// Kahn's algorithm in `O(nodes + edges)`.
+    ///
+    /// Returns the dense indices in topological order. If the graph has a cycle, the order doesn't contain
+    /// all nodes, and the remaining in-degrees are left in `self.in_degrees` (non-zero exactly for the unsorted
+    /// nodes), see [`Adjacency::is_sorted`].
+    pub(crate) fn kahn(&mut self) -> Vec<usize, NODES> {
+        let mut order = Vec::new();
+        self.kahn_into(&mut order);
+        order
+    }
+
+    /// [`Adjacency::kahn`] into an existing buffer, which is overwritten.
+    pub(crate) fn kahn_into(&mut self, order: &mut Vec<usize, NODES>) {
+        order.clear();
+        // can't fail: one entry per node
+        let _ = order.resize(self.len(), 0);
+        let len = kahn_csr(&self.starts, &self.targets, &mut self.in_degrees, order);
+        order.truncate(len);
+    }
+
+    /// Like [`Adjacency::kahn`], but processes the graph one frontier at a time.
+    ///
+    /// Returns the order together with the end position of every level in it: level `i` is
+    /// `order[ends[i - 1]..ends[i]]` (starting at 0 for the first level). A level contains exactly the nodes whose
+    /// predecessors are all in earlier levels.
+    pub(crate) fn kahn_levels(&mut self) -> (Vec<usize, NODES>, Vec<usize, NODES>) {
+        let mut order = self.starting_nodes();
+        let mut ends = Vec::new();
+        let mut head = 0;
+        while head < order.len() {
+            // everything pushed while processing the current level belongs to the next one
+            let end = order.len();
+            while head < end {
+                let idx = order[head];
+                head += 1;
+                // can't fail: every node is pushed at most once
+                self.remove_outgoing(idx, |succ| {
+                    let _ = order.push(succ);
+                });
+            }
+            // can't fail: every level holds at least one node
+            let _ = ends.push(end);
+        }
+        (order, ends)
+    }
+
+    /// Like [`Adjacency::kahn`], but always picks the ready node with the smallest `key(node, idx)`,
+    /// using a min-heap. `O((nodes + edges) * log(nodes))`.
+    pub(crate) fn kahn_min_by_key<K: Ord>(
+        &mut self,
+        key: impl Fn(N, usize) -> K,
+    ) -> Vec<usize, NODES> {
+        let mut ready: BinaryHeap<(K, usize), Min, NODES> = BinaryHeap::new();
+        for idx in self.starting_nodes() {
+            // can't fail: every node is pushed at most once
+            let _ = ready.push((key(self.nodes[idx], idx), idx));
+        }
+        let mut order = Vec::new();
+        while let Some((_, idx)) = ready.pop() {
+            let _ = order.push(idx);
+            for pos in self.successor_range(idx) {
+                let succ = self.targets[pos];
+                self.in_degrees[succ] -= 1;
+                if self.in_degrees[succ] == 0 {
+                    let _ = ready.push((key(self.nodes[succ], succ), succ));
+                }
+            }
+        }
+        order
+    }
+
+    /// Like [`Adjacency::kahn`], but picks the ready node that is smallest according to `cmp` by scanning
+    /// all ready nodes. If several are equally small, the one that became ready first wins.
+    /// `O(nodes² + edges)`.
+    pub(crate) fn kahn_min_by(
+        &mut self,
+        mut cmp: impl FnMut(N, N) -> Ordering,
+    ) -> Vec<usize, NODES> {
+        // kept in the order nodes became ready
+        let mut ready = self.starting_nodes();
+        let mut order = Vec::new();
+        while !ready.is_empty() {
+            let mut min = 0;
+            for pos in 1..ready.len() {
+                if cmp(self.nodes[ready[pos]], self.nodes[ready[min]]) == Ordering::Less {
+                    min = pos;
+                }
+            }
+            let idx = ready.remove(min);
+            // can't fail: every node is pushed at most once
+            let _ = order.push(idx);
+            self.remove_outgoing(idx, |succ| {
+                let _ = ready.push(succ);
+            });
+        }
+        order
+    }
+
+    /// Call `f` with every topological order (as dense indices) and `self` for translating them, by backtracking
+    /// over the choice of ready node at every step. Orders are produced in lexicographical order of dense indices.
+    ///
+    /// Expects an acyclic graph (otherwise nothing is produced) and restores `self.in_degrees` when done.
+    pub(crate) fn for_each_order(&mut self, mut f: impl FnMut(&[usize], &Self)) {
+        let len = self.len();
+        if len == 0 {
+            // the empty graph has exactly one (empty) order
+            f(&[], self);
+            return;
+        }
+        let mut used: Vec<bool, NODES> = Vec::new();
+        // can't fail: one flag per node
+        let _ = used.resize(len, false);
+        let mut order: Vec<usize, NODES> = Vec::new();
+        // per position in `order`: the dense index to continue the search for the next candidate at
+        let mut cursors: Vec<usize, NODES> = Vec::new();
+        let _ = cursors.push(0);
+        while let Some(cursor) = cursors.last_mut() {
+            let in_degrees = &self.in_degrees;
+            let candidate = (*cursor..len).find(|&idx| !used[idx] && in_degrees[idx] == 0);
+            match candidate {
+                Some(idx) => {
+                    *cursor = idx + 1;
+                    used[idx] = true;
+                    // can't fail: every node is in the order at most once
+                    let _ = order.push(idx);
+                    self.remove_outgoing(idx, |_| {});
+                    if order.len() == len {
+                        f(&order, self);
+                        // backtrack right away, the last position has no alternatives
+                        order.pop();
+                        used[idx] = false;
+                        self.restore_outgoing(idx);
+                    } else {
+                        let _ = cursors.push(0);
+                    }
+                }
+                None => {
+                    // all candidates for this position are exhausted, backtrack
+                    cursors.pop();
+                    if let Some(idx) = order.pop() {
+                        used[idx] = false;
+                        self.restore_outgoing(idx);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Length (in edges) of the longest path ending at each node, plus the predecessor on such a path
+    /// (`usize::MAX` for nodes without one). `order` must be a complete topological order.
+    pub(crate) fn longest_paths(&self, order: &[usize]) -> (Vec<usize, NODES>, Vec<usize, NODES>) {
+        let mut lengths: Vec<usize, NODES> = Vec::new();
+        let mut predecessors: Vec<usize, NODES> = Vec::new();
+        // can't fail: one entry per node
+        let _ = lengths.resize(self.len(), 0);
+        let _ = predecessors.resize(self.len(), usize::MAX);
+        for &idx in order {
+            for &succ in self.successors(idx) {
+                if lengths[idx] + 1 > lengths[succ] {
+                    lengths[succ] = lengths[idx] + 1;
+                    predecessors[succ] = idx;
+                }
+            }
+        }
+        (lengths, predecessors)
+    }
+
+    /// Length (in edges) of the longest path starting at each node. `order` must be a complete topological order.
+    pub(crate) fn heights(&self, order: &[usize]) -> Vec<usize, NODES> {
+        let mut heights: Vec<usize, NODES> = Vec::new();
+        // can't fail: one entry per node
+        let _ = heights.resize(self.len(), 0);
+        // successors come later in `order`, so they are done by the time we get to their predecessors
+        for &idx in order.iter().rev() {
+            heights[idx] = self
+                .successors(idx)
+                .iter()
+                .map(|&succ| heights[succ] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        heights
+    }
+
+    /// Breadth-first search from `starts`, calling `visit` once for every node reachable from any of them via at
+    /// least one edge, in BFS order. A start node itself is only visited if it is reachable from a start node.
+    pub(crate) fn for_each_descendant(&self, starts: &[usize], mut visit: impl FnMut(usize)) {
+        let mut seen: Vec<bool, NODES> = Vec::new();
+        // can't fail: one flag per node
+        let _ = seen.resize(self.len(), false);
+        // doubles as FIFO queue: everything past `head` is seen but not yet expanded
+        let mut queue: Vec<usize, NODES> = Vec::new();
+        let mut head = 0;
+        let mut starts = starts.iter().copied();
+        loop {
+            let expand = match starts.next() {
+                Some(idx) => idx,
+                None => match queue.get(head) {
+                    Some(&idx) => {
+                        head += 1;
+                        idx
+                    }
+                    None => break,
+                },
+            };
+            for &succ in self.successors(expand) {
+                if !seen[succ] {
+                    seen[succ] = true;
+                    // can't fail: every node is pushed at most once
+                    let _ = queue.push(succ);
+                    visit(succ);
+                }
+            }
+        }
+    }
+
+    /// Iterative depth-first search over all nodes (in index order), returning them in post-order:
+    /// every node comes after all of its descendants. `O(nodes + edges)`.
+    ///
+    /// Returns `Error::Cycle` as soon as an edge back to a node on the current DFS path is found.
+    pub(crate) fn dfs_post_order(&self) -> Result<Vec<usize, NODES>, Error<N, EDGES>> {
+        const UNVISITED: u8 = 0;
+        const ON_PATH: u8 = 1;
+        const DONE: u8 = 2;
+        let mut states: Vec<u8, NODES> = Vec::new();
+        // can't fail: one state per node
+        let _ = states.resize(self.len(), UNVISITED);
+        // the current DFS path: (node, number of successors already explored)
+        let mut path: Vec<(usize, usize), NODES> = Vec::new();
+        let mut order = Vec::new();
+
+        for root in 0..self.len() {
+            if states[root] != UNVISITED {
+                continue;
+            }
+            states[root] = ON_PATH;
+            // can't fail: every node is on the path at most once
+            let _ = path.push((root, 0));
+            while let Some((idx, explored)) = path.last_mut() {
+                let idx = *idx;
+                match self.successors(idx).get(*explored) {
+                    Some(&succ) => {
+                        *explored += 1;
+                        match states[succ] {
+                            UNVISITED => {
+                                states[succ] = ON_PATH;
+                                let _ = path.push((succ, 0));
+                            }
+                            ON_PATH => {
+                                // back edge: the path from `succ` to `idx` plus this edge is a cycle
+                                let start = path.iter().position(|&(n, _)| n == succ).unwrap_or(0);
+                                let mut cycle = Vec::new();
+                                let needed = path.len() - start;
+                                for &(n, _) in &path[start..] {
+                                    cycle
+                                        .push(self.nodes[n])
+                                        .map_err(|_| Error::OverCapacity { needed })?;
+                                }
+                                return Err(Error::Cycle(cycle));
+                            }
+                            _ => {}
+                        }
+                    }
+                    None => {
+                        states[idx] = DONE;
+                        path.pop();
+                        let _ = order.push(idx);
+                    }
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    /// Tarjan's strongly connected components algorithm, with an explicit stack instead of recursion.
+    /// `O(nodes + edges)`.
+    ///
+    /// Calls `on_component` with the dense indices of every component, starting with the member discovered first.
+    /// Components are reported in reverse topological order: no component has edges to one reported after it.
+    pub(crate) fn tarjan(&self, mut on_component: impl FnMut(&[usize])) {
+        const UNVISITED: usize = usize::MAX;
+        let len = self.len();
+        // discovery index and lowest discovery index reachable via the DFS subtree plus one back edge
+        let mut discovered: Vec<usize, NODES> = Vec::new();
+        let mut lowlinks: Vec<usize, NODES> = Vec::new();
+        let mut on_stack: Vec<bool, NODES> = Vec::new();
+        // can't fail: one entry per node
+        let _ = discovered.resize(len, UNVISITED);
+        let _ = lowlinks.resize(len, UNVISITED);
+        let _ = on_stack.resize(len, false);
+        // nodes that are visited but not yet assigned to a component
+        let mut stack: Vec<usize, NODES> = Vec::new();
+        // the current DFS path: (node, number of successors already explored)
+        let mut path: Vec<(usize, usize), NODES> = Vec::new();
+        let mut counter = 0;
+
+        for root in 0..len {
+            if discovered[root] != UNVISITED {
+                continue;
+            }
+            let mut visit = Some(root);
+            loop {
+                if let Some(idx) = visit.take() {
+                    discovered[idx] = counter;
+                    lowlinks[idx] = counter;
+                    counter += 1;
+                    on_stack[idx] = true;
+                    // can't fail: every node is on each stack at most once
+                    let _ = stack.push(idx);
+                    let _ = path.push((idx, 0));
+                }
+                let Some((idx, explored)) = path.last_mut() else {
+                    break;
+                };
+                let idx = *idx;
+                if let Some(&succ) = self.successors(idx).get(*explored) {
+                    *explored += 1;
+                    if discovered[succ] == UNVISITED {
+                        visit = Some(succ);
+                    } else if on_stack[succ] {
+                        lowlinks[idx] = lowlinks[idx].min(discovered[succ]);
+                    }
+                    continue;
+                }
+                // all successors explored: return to the parent
+                path.pop();
+                if let Some(&(parent, _)) = path.last() {
+                    lowlinks[parent] = lowlinks[parent].min(lowlinks[idx]);
+                }
+                if lowlinks[idx] == discovered[idx] {
+                    // `idx` is the root of a component consisting of everything above it on the stack
+                    // unwrap safety: `idx` is on the stack until its component is complete
+                    let start = stack.iter().rposition(|&n| n == idx).unwrap();
+                    for &member in &stack[start..] {
+                        on_stack[member] = false;
+                    }
+                    on_component(&stack[start..]);
+                    stack.truncate(start);
+                }
+            }
+        }
+    }
+
+    /// Johnson's algorithm, with an explicit stack instead of recursion: calls `on_cycle` with every elementary cycle
+    /// (as dense indices, starting at its smallest one). Parallel edges don't produce duplicate cycles.
+    ///
+    /// Runs the search from every start node within the nodes not below it, without Johnson's restriction to the
+    /// start's strongly connected component. Blocking still makes each search visit a node at most once while it
+    /// finds no cycle, so this takes `O((nodes + edges) * (cycles + nodes))`.
+    pub(crate) fn for_each_cycle(&self, mut on_cycle: impl FnMut(&[usize])) {
+        let mut blocked: Vec<bool, NODES> = Vec::new();
+        // `(w, v)`: unblock `v` once `w` is unblocked. Only added for edges from `v` to `w`, each at most once
+        let mut blocked_by: Vec<(usize, usize), EDGES> = Vec::new();
+        // the current path, each node with the position in its successors to continue at, and whether a cycle
+        // through it has been found
+        let mut frames: Vec<(usize, usize, bool), NODES> = Vec::new();
+        let mut path: Vec<usize, NODES> = Vec::new();
+        for start in 0..self.len() {
+            blocked.clear();
+            // can't fail: one flag per node
+            let _ = blocked.resize(self.len(), false);
+            blocked_by.clear();
+            blocked[start] = true;
+            // can't fail: every node is on the path at most once
+            let _ = frames.push((start, 0, false));
+            let _ = path.push(start);
+            while let Some(&(node, next, found)) = frames.last() {
+                let top = frames.len() - 1;
+                let successors = self.successors(node);
+                // skip nodes below `start`, and repeated successors due to parallel edges
+                let pos = (next..successors.len()).find(|&pos| {
+                    successors[pos] >= start && !successors[..pos].contains(&successors[pos])
+                });
+                if let Some(pos) = pos {
+                    frames[top].1 = pos + 1;
+                    let succ = successors[pos];
+                    if succ == start {
+                        on_cycle(&path);
+                        frames[top].2 = true;
+                    } else if !blocked[succ] {
+                        blocked[succ] = true;
+                        let _ = frames.push((succ, 0, false));
+                        let _ = path.push(succ);
+                    }
+                    continue;
+                }
+                frames.pop();
+                path.pop();
+                if found {
+                    unblock::<EDGES, NODES>(node, &mut blocked, &mut blocked_by);
+                } else {
+                    // stay blocked until a successor gets unblocked
+                    for &succ in successors {
+                        if succ >= start && !blocked_by.contains(&(succ, node)) {
+                            // can't fail: at most one entry per edge
+                            let _ = blocked_by.push((succ, node));
+                        }
+                    }
+                }
+                if let Some(parent) = frames.last_mut() {
+                    parent.2 |= found;
+                }
+            }
+        }
+    }
+
+    /// After [`Adjacency::kahn`]: whether `node` made it into the order.
+    pub(crate) fn is_sorted(&self, node: N) -> bool {
+        self.index(node)
+            .is_some_and(|idx| self.in_degrees[idx] == 0)
+    }
+}
+
+/// Unblock `node` and, transitively, everything waiting for it in `blocked_by`, see [`Adjacency::for_each_cycle`].
+fn unblock<const EDGES: usize, const NODES: usize>(
+    node: usize,
+    blocked: &mut [bool],
+    blocked_by: &mut Vec<(usize, usize), EDGES>,
+) {
+    blocked[node] = false;
+    let mut stack: Vec<usize, NODES> = Vec::new();
+    // can't fail: a node is only pushed when it changes from blocked to unblocked
+    let _ = stack.push(node);
+    while let Some(w) = stack.pop() {
+        let mut pos = 0;
+        while pos < blocked_by.len() {
+            if blocked_by[pos].0 != w {
+                pos += 1;
+                continue;
+            }
+            let (_, v) = blocked_by.swap_remove(pos);
+            if blocked[v] {
+                blocked[v] = false;
+                let _ = stack.push(v);
+            }
+        }
+    }
+}
+
+/// Fill a compressed sparse row layout from edges given as pairs of dense indices. Shared with `DynGraph`.
+///
+/// `starts` and `in_degrees` need one zeroed entry per node, `targets` one entry per edge. Afterwards the successors
+/// of node `i` are `targets[starts[i]..starts[i + 1]]` (or until the end for the last node), in edge order.
+pub(crate) fn fill_csr(
+    edges: impl DoubleEndedIterator<Item = (usize, usize)> + Clone,
+    starts: &mut [usize],
+    targets: &mut [usize],
+    in_degrees: &mut [usize],
+) {
+    // count out- and in-degrees. `starts` holds the out-degrees until it's turned into offsets below
+    for (from, to) in edges.clone() {
+        starts[from] += 1;
+        in_degrees[to] += 1;
+    }
+
+    // inclusive prefix sum: out-degrees -> end offsets
+    let mut offset = 0;
+    for start in starts.iter_mut() {
+        offset += *start;
+        *start = offset;
+    }
+
+    // place the targets back to front, moving each end offset down to the start offset of its slot.
+    // Going backwards keeps edge order within a slot
+    for (from, to) in edges.rev() {
+        starts[from] -= 1;
+        targets[starts[from]] = to;
+    }
+}
+
+/// Kahn's algorithm in `O(nodes + edges)` on a layout filled by [`fill_csr`]. Shared with `DynGraph`.
+///
+/// Writes the topological order into `order` (one entry per node) and returns its length, which is less than the
+/// number of nodes if the graph has a cycle. `in_degrees` is consumed: afterwards it is non-zero exactly for the
+/// unsorted nodes.
+pub(crate) fn kahn_csr(
+    starts: &[usize],
+    targets: &[usize],
+    in_degrees: &mut [usize],
+    order: &mut [usize],
+) -> usize {
+    // the output doubles as FIFO queue of starting nodes: everything past `head` is ready but not yet processed
+    let mut len = 0;
+    for (idx, &in_degree) in in_degrees.iter().enumerate() {
+        if in_degree == 0 {
+            order[len] = idx;
+            len += 1;
+        }
+    }
+    let mut head = 0;
+    while head < len {
+        let idx = order[head];
+        head += 1;
+        let end = starts.get(idx + 1).copied().unwrap_or(targets.len());
+        for &succ in &targets[starts[idx]..end] {
+            in_degrees[succ] -= 1;
+            if in_degrees[succ] == 0 {
+                order[len] = succ;
+                len += 1;
+            }
+        }
+    }
+    len
+}
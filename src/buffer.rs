@@ -0,0 +1,40 @@
+//! Growable storage abstraction, so algorithms can be shared between `heapless` and `alloc` vectors.
+
+use core::ops::DerefMut;
+
+pub(crate) trait Buffer<T>: DerefMut<Target = [T]> {
+    /// Append `value`, handing it back if there's no room.
+    fn try_push(&mut self, value: T) -> Result<(), T>;
+    fn clear(&mut self);
+    fn truncate(&mut self, len: usize);
+}
+
+impl<T, const N: usize> Buffer<T> for heapless::Vec<T, N> {
+    fn try_push(&mut self, value: T) -> Result<(), T> {
+        self.push(value)
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.truncate(len)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Buffer<T> for alloc::vec::Vec<T> {
+    fn try_push(&mut self, value: T) -> Result<(), T> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.truncate(len)
+    }
+}
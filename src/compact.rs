@@ -0,0 +1,141 @@
+//! Node id compaction, see [`Graph::compact`].
+
+use core::hash::Hash;
+
+use crate::{node_index::NodeIndex, Edge, Error, Graph};
+
+/// Translation between original node ids and the dense ids of a [`Graph::compact`]ed graph.
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Clone)]
+pub struct Mapping<N, const NODES: usize> {
+    /// original id <-> dense id
+    nodes: NodeIndex<N, NODES>,
+}
+
+/// Result of [`Graph::compact`]: the renumbered graph and the mapping to the original ids.
+pub type Compacted<N, const EDGES: usize, const NODES: usize> =
+    (Graph<usize, EDGES, NODES>, Mapping<N, NODES>);
+
+// by hand, see `NodeIndex`
+#[cfg(feature = "defmt-03")]
+impl<N: defmt::Format, const NODES: usize> defmt::Format for Mapping<N, NODES> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Mapping {{ nodes: {} }}", &*self.nodes)
+    }
+}
+
+impl<N: Copy + Eq + Hash, const NODES: usize> Mapping<N, NODES> {
+    /// The dense id of the original node `node`, if it is part of the graph.
+    pub fn to_dense(&self, node: N) -> Option<usize> {
+        self.nodes.index_of(node)
+    }
+
+    /// The original id of dense id `idx`, if it is in range.
+    pub fn to_original(&self, idx: usize) -> Option<N> {
+        self.nodes.get(idx).copied()
+    }
+
+    /// All original ids, indexed by dense id.
+    pub fn originals(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// Number of mapped nodes, i.e. the dense ids are `0..len()`.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The dense id of `node`, assigning the next one if it's new. `None` if full.
+    fn insert(&mut self, node: N) -> Option<usize> {
+        self.nodes.insert(node)
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Graph<N, EDGES, NODES> {
+    /// Renumber the nodes to `0..node_count`, consuming self.
+    ///
+    /// Returns the renumbered graph together with the [`Mapping`] between old and new ids. Dense ids are assigned
+    /// in order of first appearance (added nodes first, then edge endpoints, see the crate docs on ordering), so the
+    /// compacted graph sorts exactly like the original one. Its sorted output can index directly into arrays of
+    /// per-node data. Returns `Error::OverCapacity` if there are more than `NODES` distinct nodes.
+    ///
+    /// ```rust
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u32, 4>::from_array([(5000, 100), (42, 5000)]);
+    /// let (compact, mapping) = graph.compact().ok().unwrap();
+    /// let sorted = compact.into_topo_sorted().ok().unwrap();
+    /// assert_eq!(&[2, 0, 1], sorted.as_slice());
+    /// assert_eq!(Some(42), mapping.to_original(2));
+    /// assert_eq!(Some(1), mapping.to_dense(100));
+    /// ```
+    pub fn compact(self) -> Result<Compacted<N, EDGES, NODES>, Error<N, EDGES>> {
+        let mut mapping = Mapping {
+            nodes: NodeIndex::new(),
+        };
+        let mut compact = Graph::new();
+        let over_capacity = || Error::nodes_over_capacity(&self.nodes, &self.edges);
+        for &node in &self.nodes {
+            let idx = mapping.insert(node).ok_or_else(over_capacity)?;
+            // can't fail: there are at most as many added nodes as in self
+            let _ = compact.add_node(idx);
+        }
+        for edge in &self.edges {
+            let from = mapping.insert(edge.from).ok_or_else(over_capacity)?;
+            let to = mapping.insert(edge.to).ok_or_else(over_capacity)?;
+            // can't fail: there are at most as many edges as in self
+            let _ = compact.insert_edge(Edge::new(from, to));
+        }
+        Ok((compact, mapping))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heapless::Vec;
+
+    #[test]
+    fn compact() {
+        let mut graph = Graph::<u32, 8>::from_array([(100, 5000), (42, 100), (5000, 7), (42, 7)]);
+        graph.add_node(9).unwrap();
+        let (compact, mapping) = graph.clone().compact().unwrap();
+        assert_eq!(
+            &[
+                Edge::new(1, 2),
+                Edge::new(3, 1),
+                Edge::new(2, 4),
+                Edge::new(3, 4)
+            ],
+            compact.edges()
+        );
+        assert_eq!(&[9, 100, 5000, 42, 7], mapping.originals());
+        assert_eq!(5, mapping.len());
+        for (idx, &node) in mapping.originals().iter().enumerate() {
+            assert_eq!(Some(idx), mapping.to_dense(node));
+            assert_eq!(Some(node), mapping.to_original(idx));
+        }
+        assert_eq!(None, mapping.to_dense(1));
+        assert_eq!(None, mapping.to_original(5));
+
+        // sorts the same way
+        let sorted = compact.into_topo_sorted().unwrap();
+        let translated: Vec<u32, 8> = sorted
+            .iter()
+            .map(|&idx| mapping.to_original(idx).unwrap())
+            .collect();
+        assert_eq!(graph.into_topo_sorted().unwrap(), translated);
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let graph = Graph::<u32, 4, 2>::from_array([(0, 1), (1, 2)]);
+        assert!(matches!(
+            graph.compact(),
+            Err(Error::OverCapacity { needed: 3 })
+        ));
+    }
+}
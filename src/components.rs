@@ -0,0 +1,376 @@
+//! Connected components, see [`Graph::scc`] and [`Graph::weak_components`].
+
+use core::hash::Hash;
+
+use heapless::Vec;
+
+use crate::{adjacency::Adjacency, Edge, Error, Graph};
+
+/// Result of [`Graph::scc`] and [`Graph::weak_components`]: the nodes grouped by component.
+pub type Components<N, const NODES: usize> = Vec<Vec<N, NODES>, NODES>;
+
+/// Result of [`Graph::condensation`]: the component graph and the components its nodes stand for.
+pub type Condensation<N, const EDGES: usize, const NODES: usize> =
+    (Graph<N, EDGES, NODES>, Components<N, NODES>);
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Graph<N, EDGES, NODES> {
+    /// Partition the nodes into strongly connected components, consuming self.
+    ///
+    /// Two nodes are in the same component iff each is reachable from the other, so every component with more
+    /// than one node (or a single node with a self-loop) is a tangle of cycles, which is handy for diagnosing why a
+    /// sort failed. Acyclic graphs only have singleton components.
+    ///
+    /// Uses Tarjan's algorithm without recursion in `O(nodes + edges)`. Components are returned in topological
+    /// order: edges between components only ever point to a later one. Like [`crate::Levels`], the result takes
+    /// `O(NODES²)` memory.
+    ///
+    /// ```rust
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 8>::from_array([(0, 1), (1, 2), (2, 1), (2, 3)]);
+    /// let components = graph.scc().ok().unwrap();
+    /// assert_eq!(3, components.len());
+    /// assert_eq!(&[0], components[0].as_slice());
+    /// assert_eq!(&[1, 2], components[1].as_slice());
+    /// assert_eq!(&[3], components[2].as_slice());
+    /// ```
+    pub fn scc(self) -> Result<Components<N, NODES>, Error<N, EDGES>> {
+        let adjacency: Adjacency<N, EDGES, NODES> = Adjacency::new(&self.nodes, &self.edges)?;
+        let mut components: Components<N, NODES> = Vec::new();
+        adjacency.tarjan(|component| {
+            // can't fail: there are at most as many components as nodes
+            let _ = components.push(component.iter().map(|&idx| adjacency.node(idx)).collect());
+        });
+        // Tarjan finds sinks first
+        components.reverse();
+        Ok(components)
+    }
+
+    /// Collapse every strongly connected component into a single node, consuming self.
+    ///
+    /// Each component is represented by its first node as returned by [`Graph::scc`], i.e. the member that was
+    /// reached first by the depth-first search. The returned components map representatives back to all members:
+    /// `components[i][0]` is the representative of `components[i]`. The condensation has an edge between two
+    /// representatives iff the original graph has at least one edge between their components (without
+    /// duplicates), and contains components without such edges as isolated nodes.
+    ///
+    /// The result is always acyclic, so it can be sorted even if the original graph can't.
+    ///
+    /// ```rust
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 8>::from_array([(0, 1), (1, 2), (2, 1), (2, 3)]);
+    /// assert!(graph.clone().into_topo_sorted().is_err());
+    /// let (condensation, components) = graph.condensation().ok().unwrap();
+    /// assert_eq!(&[1, 2], components[1].as_slice());
+    /// let sorted = condensation.into_topo_sorted().ok().unwrap();
+    /// assert_eq!(&[0, 1, 3], sorted.as_slice());
+    /// ```
+    pub fn condensation(self) -> Result<Condensation<N, EDGES, NODES>, Error<N, EDGES>> {
+        let adjacency: Adjacency<N, EDGES, NODES> = Adjacency::new(&self.nodes, &self.edges)?;
+        let mut components: Components<N, NODES> = Vec::new();
+        // dense index -> representative
+        let mut representatives: Vec<N, NODES> = adjacency.nodes().iter().copied().collect();
+        adjacency.tarjan(|component| {
+            let representative = adjacency.node(component[0]);
+            for &idx in component {
+                representatives[idx] = representative;
+            }
+            // can't fail: there are at most as many components as nodes
+            let _ = components.push(component.iter().map(|&idx| adjacency.node(idx)).collect());
+        });
+        components.reverse();
+
+        let mut condensation = Graph::new();
+        for edge in &self.edges {
+            // unwrap safety: all endpoints are indexed
+            let from = representatives[adjacency.index(edge.from).unwrap()];
+            let to = representatives[adjacency.index(edge.to).unwrap()];
+            if from != to {
+                // can't fail: there are at most as many edges as in the original graph
+                let _ = condensation.insert_edge_unique(Edge::new(from, to));
+            }
+        }
+        for component in &components {
+            let representative = component[0];
+            if !condensation
+                .edges
+                .iter()
+                .any(|edge| edge.from == representative || edge.to == representative)
+            {
+                // can't fail: there are at most as many components as nodes
+                let _ = condensation.add_node(representative);
+            }
+        }
+        Ok((condensation, components))
+    }
+
+    /// Partition the nodes into weakly connected components, consuming self.
+    ///
+    /// Two nodes are in the same component iff they are connected when ignoring edge direction. Each component
+    /// is an independent part of the graph that could be sorted and scheduled on its own. Components are ordered
+    /// by their first node, and contain their nodes in order of first appearance (see the crate docs on
+    /// ordering). Uses union-find over the nodes.
+    pub fn weak_components(self) -> Result<Components<N, NODES>, Error<N, EDGES>> {
+        let adjacency: Adjacency<N, EDGES, NODES> = Adjacency::new(&self.nodes, &self.edges)?;
+        let mut parents: Vec<usize, NODES> = (0..adjacency.len()).collect();
+        for from in 0..adjacency.len() {
+            for &to in adjacency.successors(from) {
+                let from = find_root(&mut parents, from);
+                let to = find_root(&mut parents, to);
+                // point the later root at the earlier one, keeping roots at their component's first node
+                parents[from.max(to)] = from.min(to);
+            }
+        }
+
+        let mut components: Components<N, NODES> = Vec::new();
+        // root -> position in `components`
+        let mut slots: Vec<usize, NODES> = Vec::new();
+        // can't fail: one entry per node
+        let _ = slots.resize(adjacency.len(), usize::MAX);
+        for idx in 0..adjacency.len() {
+            let root = find_root(&mut parents, idx);
+            if slots[root] == usize::MAX {
+                slots[root] = components.len();
+                // can't fail: there are at most as many components as nodes
+                let _ = components.push(Vec::new());
+            }
+            let _ = components[slots[root]].push(adjacency.node(idx));
+        }
+        Ok(components)
+    }
+
+    /// Whether all nodes form a single weakly connected component, see [`Graph::weak_components`].
+    ///
+    /// The empty graph counts as connected. Floods the edges without any per-node bookkeeping, so this can't fail,
+    /// but takes `O(edges²)` time.
+    pub fn is_connected(&self) -> bool {
+        if self.edges.is_empty() {
+            // add_node dedups, so there's more than one node iff there's more than one entry
+            return self.nodes.len() <= 1;
+        }
+        // every explicitly added node needs an edge, otherwise it's isolated
+        if !self.nodes.iter().all(|&node| {
+            self.edges
+                .iter()
+                .any(|edge| edge.from == node || edge.to == node)
+        }) {
+            return false;
+        }
+        let mut seen: Vec<bool, EDGES> = Vec::new();
+        // can't fail: one flag per edge
+        let _ = seen.resize(self.edges.len(), false);
+        seen[0] = true;
+        // FIFO queue of edge indices, everything past `head` is yet to be expanded
+        let mut queue: Vec<usize, EDGES> = Vec::new();
+        let _ = queue.push(0);
+        let mut head = 0;
+        while let Some(&current) = queue.get(head) {
+            head += 1;
+            let Edge { from, to } = self.edges[current];
+            for (idx, edge) in self.edges.iter().enumerate() {
+                let touches = [from, to].contains(&edge.from) || [from, to].contains(&edge.to);
+                if touches && !seen[idx] {
+                    seen[idx] = true;
+                    let _ = queue.push(idx);
+                }
+            }
+        }
+        queue.len() == self.edges.len()
+    }
+
+    /// Whether the graph is a forest, i.e. acyclic with at most one edge pointing to each node.
+    ///
+    /// Every node then has at most one parent, so each weak component is a tree hanging off a single root. The
+    /// empty graph is a forest (without any trees). Can't fail, but takes `O(edges²)` time.
+    pub fn is_forest(&self) -> bool {
+        self.edges.iter().all(|edge| self.in_degree(edge.to) == 1) && self.is_dag()
+    }
+
+    /// Whether the graph is a single tree, i.e. a [forest](Graph::is_forest) that [is connected](Graph::is_connected).
+    ///
+    /// A tree has exactly one root, so unlike for [`Graph::is_connected`] the empty graph doesn't count, while a
+    /// single isolated node does. Can't fail, but takes `O(edges²)` time.
+    pub fn is_tree(&self) -> bool {
+        (!self.edges.is_empty() || !self.nodes.is_empty())
+            && self.is_forest()
+            && self.is_connected()
+    }
+}
+
+/// Union-find lookup with path halving.
+fn find_root(parents: &mut [usize], mut idx: usize) -> usize {
+    while parents[idx] != idx {
+        parents[idx] = parents[parents[idx]];
+        idx = parents[idx];
+    }
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acyclic() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        let components = graph.scc().unwrap();
+        assert_eq!(4, components.len());
+        assert!(components.iter().all(|component| component.len() == 1));
+        // components are in topological order
+        let order: Vec<usize, 8> = components.iter().map(|component| component[0]).collect();
+        assert_eq!(&[0, 2, 1, 3], order.as_slice());
+
+        assert!(Graph::<usize, 8>::new().scc().unwrap().is_empty());
+    }
+
+    #[test]
+    fn two_cycles() {
+        let graph = Graph::<usize, 16>::from_array([
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 4),
+            (7, 7),
+        ]);
+        let components = graph.scc().unwrap();
+        assert_eq!(4, components.len());
+        assert_eq!(&[7], components[0].as_slice());
+        assert_eq!(&[0, 1, 2], components[1].as_slice());
+        assert_eq!(&[3], components[2].as_slice());
+        assert_eq!(&[4, 5, 6], components[3].as_slice());
+    }
+
+    #[test]
+    fn nested_cycles() {
+        // 1 -> 2 -> 3 -> 1 and 2 -> 4 -> 2 share node 2
+        let graph =
+            Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (3, 1), (2, 4), (4, 2), (4, 5)]);
+        let components = graph.scc().unwrap();
+        assert_eq!(3, components.len());
+        assert_eq!(&[0], components[0].as_slice());
+        assert_eq!(&[1, 2, 3, 4], components[1].as_slice());
+        assert_eq!(&[5], components[2].as_slice());
+    }
+
+    #[test]
+    fn condensation() {
+        let graph = Graph::<usize, 16>::from_array([
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (1, 3),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 4),
+            (7, 7),
+        ]);
+        let (condensation, components) = graph.condensation().unwrap();
+        assert_eq!(&[Edge::new(0, 3), Edge::new(3, 4)], condensation.edges());
+        let sorted = condensation.into_topo_sorted().unwrap();
+        assert_eq!(&[7, 0, 3, 4], sorted.as_slice());
+        assert_eq!(4, components.len());
+        assert_eq!(&[7], components[0].as_slice());
+        assert_eq!(&[0, 1, 2], components[1].as_slice());
+        assert_eq!(&[3], components[2].as_slice());
+        assert_eq!(&[4, 5, 6], components[3].as_slice());
+
+        // acyclic graphs are their own condensation, up to duplicate edges
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        let (condensation, _) = graph.clone().condensation().unwrap();
+        assert_eq!(graph, condensation);
+    }
+
+    #[test]
+    fn weak_components() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (2, 3), (4, 3), (1, 5), (6, 5)]);
+        graph.add_node(7).unwrap();
+        let components = graph.weak_components().unwrap();
+        assert_eq!(3, components.len());
+        assert_eq!(&[7], components[0].as_slice());
+        assert_eq!(&[0, 1, 5, 6], components[1].as_slice());
+        assert_eq!(&[2, 3, 4], components[2].as_slice());
+
+        // direction doesn't matter, cycles don't either
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (2, 1), (2, 3), (3, 2)]);
+        let components = graph.weak_components().unwrap();
+        assert_eq!(1, components.len());
+        assert_eq!(&[0, 1, 2, 3], components[0].as_slice());
+
+        assert!(Graph::<usize, 8>::new()
+            .weak_components()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn is_connected() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (2, 1), (3, 2)]);
+        assert!(graph.is_connected());
+        graph.add_node(3).unwrap();
+        assert!(graph.is_connected());
+        graph.add_node(4).unwrap();
+        assert!(!graph.is_connected());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (2, 3)]);
+        assert!(!graph.is_connected());
+
+        let mut graph = Graph::<usize, 8>::new();
+        assert!(graph.is_connected());
+        graph.add_node(0).unwrap();
+        assert!(graph.is_connected());
+        graph.add_node(1).unwrap();
+        assert!(!graph.is_connected());
+    }
+
+    #[test]
+    fn is_forest_and_tree() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (2, 3), (2, 4)]);
+        assert!(graph.is_forest());
+        assert!(graph.is_tree());
+        graph.add_node(5).unwrap();
+        assert!(graph.is_forest());
+        assert!(!graph.is_tree());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (2, 3)]);
+        assert!(graph.is_forest());
+        assert!(!graph.is_tree());
+
+        // diamonds and parallel edges give a node two parents
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        assert!(!graph.is_forest());
+        assert!(!graph.is_tree());
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 1)]);
+        assert!(!graph.is_forest());
+
+        // a cycle has in-degree 1 everywhere
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 0)]);
+        assert!(!graph.is_forest());
+        let graph = Graph::<usize, 8>::from_array([(0, 0)]);
+        assert!(!graph.is_forest());
+
+        let mut graph = Graph::<usize, 8>::new();
+        assert!(graph.is_forest());
+        assert!(!graph.is_tree());
+        graph.add_node(0).unwrap();
+        assert!(graph.is_tree());
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let graph = Graph::<usize, 2>::from_array([(0, 1), (1, 2)]);
+        assert_eq!(Err(Error::OverCapacity { needed: 3 }), graph.clone().scc());
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.clone().condensation()
+        );
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.weak_components()
+        );
+    }
+}
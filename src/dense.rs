@@ -0,0 +1,282 @@
+//! Graph over small, dense node ids with bitsets instead of per-node tables, see [`GraphDense`].
+
+use heapless::Vec;
+
+use crate::{find_cycle, Edge, Error};
+
+/// Fixed-size set of node ids `0..32 * WORDS`.
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Clone, Copy)]
+struct Bits<const WORDS: usize>([u32; WORDS]);
+
+impl<const WORDS: usize> Bits<WORDS> {
+    const fn new() -> Self {
+        Self([0; WORDS])
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        self.0[node / 32] & (1 << (node % 32)) != 0
+    }
+
+    fn insert(&mut self, node: usize) {
+        self.0[node / 32] |= 1 << (node % 32);
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// The members in ascending order. Skips empty words, so this takes `O(WORDS + len)` time.
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(idx, &word)| {
+            let mut rest = word;
+            core::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                // clear the lowest set bit
+                rest &= rest - 1;
+                Some(32 * idx + bit)
+            })
+        })
+    }
+
+    /// The members of `self` that aren't in `other`.
+    fn difference(&self, other: &Self) -> Self {
+        let mut result = *self;
+        for (word, other) in result.0.iter_mut().zip(other.0) {
+            *word &= !other;
+        }
+        result
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        for (word, other) in self.0.iter_mut().zip(other.0) {
+            *word |= other;
+        }
+    }
+}
+
+/// A graph over `usize` node ids below [`GraphDense::MAX_NODE`], for MCUs where every byte counts.
+///
+/// [`crate::Graph`] translates arbitrary node ids to dense indices and keeps several words of temporary data per
+/// node while sorting. If your ids are small and dense anyway, `GraphDense` needs only a few bitsets of
+/// `WORDS` `u32`s each, i.e. a couple of *bits* per possible node, plus the edges and the output buffer. Node ids
+/// have to be below `MAX_NODE = 32 * WORDS` though, so pick `WORDS` as `MAX_NODE.div_ceil(32)`:
+///
+/// ```
+/// use heapless::Vec;
+/// use heapless_topo::{Edge, GraphDense};
+/// // node ids 0..100
+/// let mut graph = GraphDense::<{ 100usize.div_ceil(32) }, 16>::new();
+/// graph.insert_edge(Edge::new(99, 3)).ok().unwrap();
+/// graph.insert_edge(Edge::new(3, 0)).ok().unwrap();
+/// let mut sorted: Vec<usize, 8> = Vec::new();
+/// graph.topo_sort_into(&mut sorted).ok().unwrap();
+/// assert_eq!(&[99, 3, 0], sorted.as_slice());
+/// ```
+///
+/// The tradeoff is time: without per-node in-degree counts, each round of the sort rescans all edges, so sorting
+/// takes `O(depth * (edges + MAX_NODE / 32) + nodes)` rather than `O(nodes + edges)`. The order differs from `Graph` as
+/// well: it is level by level (see [`crate::Graph::into_levels`]), ascending by id within each level, so it only
+/// depends on the graph's structure.
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Clone)]
+pub struct GraphDense<const WORDS: usize, const EDGES: usize> {
+    edges: Vec<Edge<usize>, EDGES>,
+    // explicitly added nodes, see `add_node`
+    nodes: Bits<WORDS>,
+}
+
+impl<const WORDS: usize, const EDGES: usize> Default for GraphDense<WORDS, EDGES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WORDS: usize, const EDGES: usize> GraphDense<WORDS, EDGES> {
+    /// Node ids must be below this.
+    pub const MAX_NODE: usize = 32 * WORDS;
+
+    pub const fn new() -> Self {
+        Self {
+            edges: Vec::new(),
+            nodes: Bits::new(),
+        }
+    }
+
+    /// Insert an edge into the graph. No duplicate check is performed.
+    /// Returns `Error::OverCapacity` if full, or if an endpoint is not below [`GraphDense::MAX_NODE`], in which case
+    /// `needed` is the `MAX_NODE` it would take.
+    pub fn insert_edge(&mut self, edge: Edge<usize>) -> Result<(), Error<usize, EDGES>> {
+        Self::check_node(edge.from.max(edge.to))?;
+        self.edges
+            .push(edge)
+            .map_err(|_| Error::OverCapacity { needed: EDGES + 1 })
+    }
+
+    /// Register a node, so it is part of the sort even if no edge references it, see [`crate::Graph::add_node`].
+    /// Returns `Error::OverCapacity` if `node` is not below [`GraphDense::MAX_NODE`].
+    pub fn add_node(&mut self, node: usize) -> Result<(), Error<usize, EDGES>> {
+        Self::check_node(node)?;
+        self.nodes.insert(node);
+        Ok(())
+    }
+
+    fn check_node(node: usize) -> Result<(), Error<usize, EDGES>> {
+        if node >= Self::MAX_NODE {
+            return Err(Error::OverCapacity { needed: node + 1 });
+        }
+        Ok(())
+    }
+
+    /// The stored edges, in insertion order.
+    pub fn edges(&self) -> &[Edge<usize>] {
+        &self.edges
+    }
+
+    /// Number of stored edges, duplicates included.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Alias for [`GraphDense::edge_count`].
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// compute topological sort into a caller-provided buffer, leaving self intact.
+    ///
+    /// `out` is cleared on entry. Returns `Error::OverCapacity` if it is too small to hold all nodes, in which case
+    /// it contains a prefix of the order. On `Error::Cycle`, `out` holds the nodes that could be sorted.
+    pub fn topo_sort_into<const LEN: usize>(
+        &self,
+        out: &mut Vec<usize, LEN>,
+    ) -> Result<(), Error<usize, EDGES>> {
+        out.clear();
+        let mut present = self.nodes;
+        for edge in &self.edges {
+            present.insert(edge.from);
+            present.insert(edge.to);
+        }
+        let needed = present.len();
+        let mut sorted = Bits::<WORDS>::new();
+        loop {
+            // nodes with an incoming edge from a node that isn't sorted yet
+            let mut blocked = Bits::<WORDS>::new();
+            for edge in &self.edges {
+                if !sorted.contains(edge.from) {
+                    blocked.insert(edge.to);
+                }
+            }
+            let ready = present.difference(&sorted).difference(&blocked);
+            if ready == Bits::new() {
+                break;
+            }
+            for node in ready.iter() {
+                out.push(node).map_err(|_| Error::OverCapacity { needed })?;
+            }
+            // only mark the level sorted now, so nodes freed by it wait for the next round
+            sorted.union_with(&ready);
+        }
+        if out.len() == needed {
+            return Ok(());
+        }
+        let residual = self.edges.iter().filter(|edge| !sorted.contains(edge.from));
+        match find_cycle(residual) {
+            Ok(cycle) => Err(Error::Cycle(cycle)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits() {
+        let mut bits = Bits::<2>::new();
+        for node in [63, 0, 31, 32, 0] {
+            bits.insert(node);
+        }
+        assert_eq!(4, bits.len());
+        assert!(bits.contains(32) && !bits.contains(33));
+        assert!(bits.iter().eq([0, 31, 32, 63]));
+
+        let mut other = Bits::<2>::new();
+        other.insert(31);
+        other.insert(40);
+        assert!(bits.difference(&other).iter().eq([0, 32, 63]));
+        bits.union_with(&other);
+        assert!(bits.iter().eq([0, 31, 32, 40, 63]));
+        assert_eq!(0, Bits::<2>::new().iter().count());
+    }
+
+    #[test]
+    fn wide_shallow() {
+        // a handful of nodes spread over a large id range, two levels deep
+        let mut graph = GraphDense::<{ 4096 / 32 }, 8>::new();
+        for edge in [(4095, 0), (2048, 0), (4095, 1000), (31, 32)] {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        let mut sorted: Vec<usize, 8> = Vec::new();
+        graph.topo_sort_into(&mut sorted).unwrap();
+        assert_eq!(&[31, 2048, 4095, 0, 32, 1000], sorted.as_slice());
+    }
+
+    #[test]
+    fn levels() {
+        let mut graph = GraphDense::<1, 8>::new();
+        for edge in [(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2)] {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        graph.add_node(9).unwrap();
+        let mut out: Vec<usize, 8> = Vec::new();
+        graph.topo_sort_into(&mut out).unwrap();
+        assert_eq!(&[4, 5, 9, 0, 2, 3, 1], out.as_slice());
+    }
+
+    #[test]
+    fn err_cycle() {
+        let mut graph = GraphDense::<1, 8>::new();
+        for edge in [(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)] {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        let mut out: Vec<usize, 8> = Vec::new();
+        let expected = [2, 3, 1].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.topo_sort_into(&mut out));
+        assert_eq!(&[0], out.as_slice());
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let mut graph = GraphDense::<1, 2>::new();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 33 }),
+            graph.insert_edge(Edge::new(0, 32))
+        );
+        assert_eq!(Err(Error::OverCapacity { needed: 40 }), graph.add_node(39));
+        graph.insert_edge(Edge::new(0, 31)).unwrap();
+        graph.insert_edge(Edge::new(1, 31)).unwrap();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.insert_edge(Edge::new(2, 31))
+        );
+
+        let mut out: Vec<usize, 2> = Vec::new();
+        graph.add_node(7).unwrap();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 4 }),
+            graph.topo_sort_into(&mut out)
+        );
+        assert_eq!(&[0, 1], out.as_slice());
+    }
+}
@@ -0,0 +1,118 @@
+//! Graphviz export, see [`Graph::write_dot`].
+
+use core::fmt::{self, Display, Write};
+use core::hash::Hash;
+
+use crate::Graph;
+
+impl<N: Copy + Eq + Hash + Display, const EDGES: usize, const NODES: usize> Graph<N, EDGES, NODES> {
+    /// Write the graph in Graphviz DOT format, e.g. to paste it into a viewer while debugging.
+    ///
+    /// Edges are written in insertion order, followed by the nodes added via [`Graph::add_node`] that no edge
+    /// references. Node ids are formatted with `Display` and quoted, so they double as labels.
+    ///
+    /// ```rust
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 4>::from_array([(0, 1), (1, 2)]);
+    /// let mut dot = heapless::String::<64>::new();
+    /// graph.write_dot(&mut dot).unwrap();
+    /// assert_eq!("digraph {\n    \"0\" -> \"1\";\n    \"1\" -> \"2\";\n}\n", dot.as_str());
+    /// ```
+    pub fn write_dot<W: Write>(&self, w: &mut W) -> fmt::Result {
+        w.write_str("digraph {\n")?;
+        for edge in &self.edges {
+            writeln!(w, "    {} -> {};", Quoted(edge.from), Quoted(edge.to))?;
+        }
+        for &node in &self.nodes {
+            if !self
+                .edges
+                .iter()
+                .any(|edge| edge.from == node || edge.to == node)
+            {
+                writeln!(w, "    {};", Quoted(node))?;
+            }
+        }
+        w.write_str("}\n")
+    }
+
+    /// The graph in Graphviz DOT format, see [`Graph::write_dot`].
+    #[cfg(feature = "std")]
+    pub fn to_dot(&self) -> std::string::String {
+        let mut dot = std::string::String::new();
+        // can't fail: writing to a String is infallible
+        let _ = self.write_dot(&mut dot);
+        dot
+    }
+}
+
+/// A node id as a quoted DOT id.
+struct Quoted<N>(N);
+
+impl<N: Display> Display for Quoted<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        write!(Escaped(f), "{}", self.0)?;
+        f.write_char('"')
+    }
+}
+
+/// Escapes quotes and backslashes within a quoted DOT id.
+struct Escaped<'a, W>(&'a mut W);
+
+impl<W: Write> Write for Escaped<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '"' || c == '\\' {
+                self.0.write_char('\\')?;
+            }
+            self.0.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_edges() {
+        let graph = Graph::<usize, 4>::from_array([(0, 1), (1, 2)]);
+        let mut dot = heapless::String::<64>::new();
+        graph.write_dot(&mut dot).unwrap();
+        assert_eq!(
+            "digraph {\n    \"0\" -> \"1\";\n    \"1\" -> \"2\";\n}\n",
+            dot.as_str()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn to_dot() {
+        let graph = Graph::<usize, 4>::from_array([(0, 1), (1, 2)]);
+        assert_eq!(
+            "digraph {\n    \"0\" -> \"1\";\n    \"1\" -> \"2\";\n}\n",
+            graph.to_dot()
+        );
+    }
+
+    #[test]
+    fn isolated_nodes_and_escaping() {
+        let mut graph = Graph::<&str, 4>::from_array([("a", "b\"c")]);
+        graph.add_node("a").unwrap();
+        graph.add_node("d\\").unwrap();
+        let mut dot = heapless::String::<64>::new();
+        graph.write_dot(&mut dot).unwrap();
+        assert_eq!(
+            "digraph {\n    \"a\" -> \"b\\\"c\";\n    \"d\\\\\";\n}\n",
+            dot.as_str()
+        );
+    }
+
+    #[test]
+    fn err_buffer_too_small() {
+        let graph = Graph::<usize, 4>::from_array([(0, 1), (1, 2)]);
+        let mut dot = heapless::String::<16>::new();
+        assert!(graph.write_dot(&mut dot).is_err());
+    }
+}
@@ -0,0 +1,715 @@
+//! Heap-allocated graph without capacity limits, see [`DynGraph`].
+
+use alloc::{collections::BinaryHeap, vec, vec::Vec};
+use core::{
+    cmp::{Ordering, Reverse},
+    fmt,
+    hash::Hash,
+    ops::Range,
+};
+
+use crate::{
+    adjacency::{fill_csr, kahn_csr},
+    find_cycle_into,
+    node_index::fnv1a,
+    Edge,
+};
+
+/// Error type of [`DynGraph`]: without capacity limits, only cycles can make a sort fail.
+///
+/// Like [`crate::Error`], implements `core::error::Error` without the `std` feature.
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DynError<N> {
+    /// The graph contains a cycle, see [`crate::Error::Cycle`].
+    Cycle(Vec<N>),
+}
+
+impl<N> fmt::Display for DynError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynError::Cycle(_) => f.write_str("cycle detected"),
+        }
+    }
+}
+
+impl<N: fmt::Debug> core::error::Error for DynError<N> {}
+
+/// Like [`crate::Graph`], but backed by `alloc::vec::Vec`, for when the graph size is only known at runtime.
+///
+/// There are no capacity errors. Nodes have the same `N: Copy + Eq + Hash` bound as in `Graph`, and sorting shares
+/// its implementation with `Graph`, so both produce the same order for the same input (see the crate docs on
+/// ordering).
+///
+/// `DynGraph` covers building and editing the graph, the per-node queries (degrees, roots, leaves), cycle detection
+/// and all sort variants of `Graph`. It doesn't (yet) offer the analyses built on top of sorting, such as
+/// [`crate::Graph::scc`], [`crate::Graph::critical_path`] or [`crate::Graph::transitive_closure`], nor the
+/// `heapless`-specific methods around capacities and caller-provided buffers.
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Clone, Default)]
+pub struct DynGraph<N> {
+    edges: Vec<Edge<N>>,
+    // explicitly added nodes, see `add_node`
+    nodes: Vec<N>,
+}
+
+impl<N: Copy + Eq + Hash> DynGraph<N> {
+    pub fn new() -> Self {
+        Self {
+            edges: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Create a graph with room for `edges` edges before reallocating.
+    pub fn with_capacity(edges: usize) -> Self {
+        Self {
+            edges: Vec::with_capacity(edges),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Register a node, see [`crate::Graph::add_node`].
+    pub fn add_node(&mut self, node: N) {
+        if !self.nodes.contains(&node) {
+            self.nodes.push(node);
+        }
+    }
+
+    /// Insert an edge into the graph. No duplicate check is performed.
+    pub fn insert_edge(&mut self, edge: Edge<N>) {
+        self.edges.push(edge);
+    }
+
+    /// Check whether an edge equal to `edge` is present.
+    pub fn contains_edge(&self, edge: Edge<N>) -> bool {
+        self.edges.contains(&edge)
+    }
+
+    /// Remove the first edge equal to `edge`, keeping the order of the remaining edges.
+    /// Returns whether an edge was removed.
+    pub fn remove_edge(&mut self, edge: Edge<N>) -> bool {
+        match self.edges.iter().position(|e| *e == edge) {
+            Some(idx) => {
+                self.edges.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The stored edges, in insertion order.
+    pub fn edges(&self) -> &[Edge<N>] {
+        &self.edges
+    }
+
+    /// Number of stored edges, duplicates included.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Alias for [`DynGraph::edge_count`].
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Number of edges pointing to `node`.
+    pub fn in_degree(&self, node: N) -> usize {
+        self.edges.iter().filter(|edge| edge.to == node).count()
+    }
+
+    /// Number of edges starting at `node`.
+    pub fn out_degree(&self, node: N) -> usize {
+        self.edges.iter().filter(|edge| edge.from == node).count()
+    }
+
+    /// The nodes without incoming edges, in the same order as [`crate::Graph::roots`].
+    ///
+    /// Takes `O(nodes + edges)` time, as the degrees are counted up front.
+    pub fn roots(&self) -> Vec<N> {
+        let adjacency = self.adjacency();
+        self.ends(
+            &adjacency,
+            |edge| edge.from,
+            |idx| adjacency.in_degrees[idx] == 0,
+        )
+    }
+
+    /// The nodes without outgoing edges, in the same order as [`crate::Graph::leaves`].
+    ///
+    /// Takes `O(nodes + edges)` time, as the degrees are counted up front.
+    pub fn leaves(&self) -> Vec<N> {
+        let adjacency = self.adjacency();
+        self.ends(
+            &adjacency,
+            |edge| edge.to,
+            |idx| adjacency.successors(idx).is_empty(),
+        )
+    }
+
+    /// Shared by [`DynGraph::roots`] and [`DynGraph::leaves`]: the `endpoint`s of the edges that are `selected`,
+    /// each once, followed by the isolated nodes.
+    fn ends(
+        &self,
+        adjacency: &DynAdjacency<N>,
+        endpoint: impl Fn(&Edge<N>) -> N,
+        selected: impl Fn(usize) -> bool,
+    ) -> Vec<N> {
+        let mut seen = vec![false; adjacency.len()];
+        let mut ends = Vec::new();
+        for edge in &self.edges {
+            let idx = adjacency.index_of(endpoint(edge));
+            if !seen[idx] && selected(idx) {
+                seen[idx] = true;
+                ends.push(endpoint(edge));
+            }
+        }
+        let isolated = self.nodes.iter().copied().filter(|&node| {
+            let idx = adjacency.index_of(node);
+            adjacency.in_degrees[idx] == 0 && adjacency.successors(idx).is_empty()
+        });
+        ends.extend(isolated);
+        ends
+    }
+
+    /// Whether the graph is acyclic, i.e. whether sorting it would succeed.
+    pub fn is_dag(&self) -> bool {
+        self.find_cycle().is_none()
+    }
+
+    /// Find one cycle, or `None` if the graph is acyclic. See [`crate::Graph::find_cycle`].
+    ///
+    /// Without a capacity bound on the scratch space, this simply runs a sort in `O(nodes + edges)` time and
+    /// returns the cycle it reports, so it may name a different cycle than `Graph::find_cycle`.
+    pub fn find_cycle(&self) -> Option<Vec<N>> {
+        match self.topo_sorted() {
+            Ok(_) => None,
+            Err(DynError::Cycle(cycle)) => Some(cycle),
+        }
+    }
+
+    /// compute topological sort, consuming self. See [`crate::Graph::into_topo_sorted`].
+    pub fn into_topo_sorted(self) -> Result<Vec<N>, DynError<N>> {
+        self.topo_sorted()
+    }
+
+    /// compute reverse topological sort (sinks first, sources last), consuming self.
+    /// See [`crate::Graph::into_topo_sorted_rev`].
+    pub fn into_topo_sorted_rev(self) -> Result<Vec<N>, DynError<N>> {
+        let mut sorted = self.topo_sorted()?;
+        sorted.reverse();
+        Ok(sorted)
+    }
+
+    /// compute topological sort, leaving self intact. See [`crate::Graph::topo_sorted`].
+    pub fn topo_sorted(&self) -> Result<Vec<N>, DynError<N>> {
+        let mut sorted = Vec::new();
+        self.topo_sort_into(&mut sorted)?;
+        Ok(sorted)
+    }
+
+    /// compute topological sort into a caller-provided buffer, leaving self intact, e.g. to reuse its allocation.
+    ///
+    /// `out` is cleared on entry. On `DynError::Cycle`, `out` is left empty. See [`crate::Graph::topo_sort_into`].
+    pub fn topo_sort_into(&self, out: &mut Vec<N>) -> Result<(), DynError<N>> {
+        out.clear();
+        let mut adjacency = self.adjacency();
+        let mut order = vec![0; adjacency.len()];
+        let len = kahn_csr(
+            &adjacency.starts,
+            &adjacency.targets,
+            &mut adjacency.in_degrees,
+            &mut order,
+        );
+        order.truncate(len);
+        self.check_complete(&order, &adjacency)?;
+        out.extend(order.iter().map(|&idx| adjacency.node(idx)));
+        Ok(())
+    }
+
+    /// compute the lexicographically smallest topological sort, consuming self.
+    /// See [`crate::Graph::into_topo_sorted_lex`].
+    pub fn into_topo_sorted_lex(self) -> Result<Vec<N>, DynError<N>>
+    where
+        N: Ord,
+    {
+        let mut adjacency = self.adjacency();
+        let order = adjacency.kahn_min_by_key(|node, _| node);
+        self.collect_order(&order, &adjacency)
+    }
+
+    /// compute a stable topological sort, consuming self. See [`crate::Graph::into_topo_sorted_stable`].
+    pub fn into_topo_sorted_stable(self) -> Result<Vec<N>, DynError<N>> {
+        let mut adjacency = self.adjacency();
+        // dense indices are assigned in order of first appearance
+        let order = adjacency.kahn_min_by_key(|_, idx| idx);
+        self.collect_order(&order, &adjacency)
+    }
+
+    /// compute topological sort with custom tie-breaking, consuming self.
+    /// See [`crate::Graph::into_topo_sorted_by`], including its `O(nodes²)` worst case.
+    pub fn into_topo_sorted_by<F: FnMut(N, N) -> Ordering>(
+        self,
+        cmp: F,
+    ) -> Result<Vec<N>, DynError<N>> {
+        let mut adjacency = self.adjacency();
+        let order = adjacency.kahn_min_by(cmp);
+        self.collect_order(&order, &adjacency)
+    }
+
+    /// compute topological sort using depth-first search instead of Kahn's algorithm, consuming self.
+    /// See [`crate::Graph::into_topo_sorted_dfs`].
+    pub fn into_topo_sorted_dfs(self) -> Result<Vec<N>, DynError<N>> {
+        let adjacency = self.adjacency();
+        let order = adjacency.dfs_post_order()?;
+        Ok(order.iter().rev().map(|&idx| adjacency.node(idx)).collect())
+    }
+
+    /// group the nodes into levels of mutually independent nodes, consuming self.
+    /// See [`crate::Graph::into_levels`]; here the levels only take as much memory as they need.
+    pub fn into_levels(self) -> Result<Vec<Vec<N>>, DynError<N>> {
+        let mut adjacency = self.adjacency();
+        let (order, ends) = adjacency.kahn_levels();
+        self.check_complete(&order, &adjacency)?;
+        let mut start = 0;
+        Ok(ends
+            .into_iter()
+            .map(|end| {
+                let level = order[start..end]
+                    .iter()
+                    .map(|&idx| adjacency.node(idx))
+                    .collect();
+                start = end;
+                level
+            })
+            .collect())
+    }
+
+    fn adjacency(&self) -> DynAdjacency<N> {
+        DynAdjacency::new(&self.nodes, &self.edges)
+    }
+
+    /// Translate a complete order of dense indices to node ids, or report the cycle that stopped it.
+    fn collect_order(
+        &self,
+        order: &[usize],
+        adjacency: &DynAdjacency<N>,
+    ) -> Result<Vec<N>, DynError<N>> {
+        self.check_complete(order, adjacency)?;
+        Ok(order.iter().map(|&idx| adjacency.node(idx)).collect())
+    }
+
+    /// Turn an incomplete order of Kahn's algorithm into `DynError::Cycle`.
+    fn check_complete(
+        &self,
+        order: &[usize],
+        adjacency: &DynAdjacency<N>,
+    ) -> Result<(), DynError<N>> {
+        if order.len() == adjacency.len() {
+            return Ok(());
+        }
+        // edges whose `from` node didn't make it into the order
+        let residual = self
+            .edges
+            .iter()
+            .filter(|edge| adjacency.in_degrees[adjacency.index_of(edge.from)] != 0);
+        let mut cycle = Vec::new();
+        // can't fail: `Vec` grows as needed
+        let _ = find_cycle_into(residual, &mut cycle);
+        Err(DynError::Cycle(cycle))
+    }
+}
+
+impl<N: Copy + Eq + Hash> FromIterator<Edge<N>> for DynGraph<N> {
+    fn from_iter<T: IntoIterator<Item = Edge<N>>>(iter: T) -> Self {
+        Self {
+            edges: iter.into_iter().collect(),
+            nodes: Vec::new(),
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash> Extend<Edge<N>> for DynGraph<N> {
+    fn extend<T: IntoIterator<Item = Edge<N>>>(&mut self, iter: T) {
+        self.edges.extend(iter)
+    }
+}
+
+/// Growable counterpart of `NodeIndex`: maps node ids to dense indices in order of first appearance.
+///
+/// Open addressing with linear probing over a power-of-two number of slots, kept at most half full.
+struct DynIndex<N> {
+    /// dense index -> node id
+    nodes: Vec<N>,
+    /// dense index of the node hashed to each slot, or `usize::MAX`
+    slots: Vec<usize>,
+}
+
+impl<N: Copy + Eq + Hash> DynIndex<N> {
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            slots: Vec::new(),
+        }
+    }
+
+    /// The slot holding `node`, or the empty one it would go into. Needs at least one empty slot.
+    fn probe(&self, node: N) -> usize {
+        let mask = self.slots.len() - 1;
+        let mut slot = fnv1a(&node) as usize & mask;
+        while self.slots[slot] != usize::MAX && self.nodes[self.slots[slot]] != node {
+            slot = (slot + 1) & mask;
+        }
+        slot
+    }
+
+    /// Dense index of `node`, which must be present.
+    fn index_of(&self, node: N) -> usize {
+        self.slots[self.probe(node)]
+    }
+
+    /// Dense index of `node`, assigning the next one if it's new.
+    fn insert(&mut self, node: N) -> usize {
+        if 2 * (self.nodes.len() + 1) > self.slots.len() {
+            self.slots = vec![usize::MAX; (2 * (self.nodes.len() + 1)).next_power_of_two().max(8)];
+            for (idx, &node) in self.nodes.iter().enumerate() {
+                let slot = self.probe(node);
+                self.slots[slot] = idx;
+            }
+        }
+        let slot = self.probe(node);
+        if self.slots[slot] == usize::MAX {
+            self.slots[slot] = self.nodes.len();
+            self.nodes.push(node);
+        }
+        self.slots[slot]
+    }
+}
+
+/// Growable counterpart of `Adjacency`: the graph in compressed sparse row layout over dense indices.
+struct DynAdjacency<N> {
+    index: DynIndex<N>,
+    starts: Vec<usize>,
+    targets: Vec<usize>,
+    in_degrees: Vec<usize>,
+}
+
+impl<N: Copy + Eq + Hash> DynAdjacency<N> {
+    /// Dense indices are assigned like in `Adjacency`: added nodes first, then edge endpoints in edge order.
+    fn new(nodes: &[N], edges: &[Edge<N>]) -> Self {
+        let mut index = DynIndex::new();
+        for &node in nodes {
+            index.insert(node);
+        }
+        let pairs: Vec<(usize, usize)> = edges
+            .iter()
+            .map(|edge| (index.insert(edge.from), index.insert(edge.to)))
+            .collect();
+        let len = index.nodes.len();
+        let mut starts = vec![0; len];
+        let mut targets = vec![0; pairs.len()];
+        let mut in_degrees = vec![0; len];
+        fill_csr(
+            pairs.iter().copied(),
+            &mut starts,
+            &mut targets,
+            &mut in_degrees,
+        );
+        Self {
+            index,
+            starts,
+            targets,
+            in_degrees,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.index.nodes.len()
+    }
+
+    fn node(&self, idx: usize) -> N {
+        self.index.nodes[idx]
+    }
+
+    fn index_of(&self, node: N) -> usize {
+        self.index.index_of(node)
+    }
+
+    fn successor_range(&self, idx: usize) -> Range<usize> {
+        let end = self
+            .starts
+            .get(idx + 1)
+            .copied()
+            .unwrap_or(self.targets.len());
+        self.starts[idx]..end
+    }
+
+    fn successors(&self, idx: usize) -> &[usize] {
+        &self.targets[self.successor_range(idx)]
+    }
+
+    fn starting_nodes(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&idx| self.in_degrees[idx] == 0)
+            .collect()
+    }
+
+    /// See `Adjacency::remove_outgoing`.
+    fn remove_outgoing(&mut self, idx: usize, mut on_starting: impl FnMut(usize)) {
+        for pos in self.successor_range(idx) {
+            let succ = self.targets[pos];
+            self.in_degrees[succ] -= 1;
+            if self.in_degrees[succ] == 0 {
+                on_starting(succ);
+            }
+        }
+    }
+
+    /// See `Adjacency::kahn_levels`.
+    fn kahn_levels(&mut self) -> (Vec<usize>, Vec<usize>) {
+        let mut order = self.starting_nodes();
+        let mut ends = Vec::new();
+        let mut head = 0;
+        while head < order.len() {
+            // everything pushed while processing the current level belongs to the next one
+            let end = order.len();
+            while head < end {
+                let idx = order[head];
+                head += 1;
+                self.remove_outgoing(idx, |succ| order.push(succ));
+            }
+            ends.push(end);
+        }
+        (order, ends)
+    }
+
+    /// See `Adjacency::kahn_min_by_key`.
+    fn kahn_min_by_key<K: Ord>(&mut self, key: impl Fn(N, usize) -> K) -> Vec<usize> {
+        let mut ready: BinaryHeap<Reverse<(K, usize)>> = self
+            .starting_nodes()
+            .into_iter()
+            .map(|idx| Reverse((key(self.node(idx), idx), idx)))
+            .collect();
+        let mut order = Vec::new();
+        while let Some(Reverse((_, idx))) = ready.pop() {
+            order.push(idx);
+            for pos in self.successor_range(idx) {
+                let succ = self.targets[pos];
+                self.in_degrees[succ] -= 1;
+                if self.in_degrees[succ] == 0 {
+                    ready.push(Reverse((key(self.node(succ), succ), succ)));
+                }
+            }
+        }
+        order
+    }
+
+    /// See `Adjacency::kahn_min_by`.
+    fn kahn_min_by(&mut self, mut cmp: impl FnMut(N, N) -> Ordering) -> Vec<usize> {
+        // kept in the order nodes became ready
+        let mut ready = self.starting_nodes();
+        let mut order = Vec::new();
+        while !ready.is_empty() {
+            let mut min = 0;
+            for pos in 1..ready.len() {
+                if cmp(self.node(ready[pos]), self.node(ready[min])) == Ordering::Less {
+                    min = pos;
+                }
+            }
+            let idx = ready.remove(min);
+            order.push(idx);
+            self.remove_outgoing(idx, |succ| ready.push(succ));
+        }
+        order
+    }
+
+    /// See `Adjacency::dfs_post_order`.
+    fn dfs_post_order(&self) -> Result<Vec<usize>, DynError<N>> {
+        const UNVISITED: u8 = 0;
+        const ON_PATH: u8 = 1;
+        const DONE: u8 = 2;
+        let mut states = vec![UNVISITED; self.len()];
+        // the current DFS path: (node, number of successors already explored)
+        let mut path: Vec<(usize, usize)> = Vec::new();
+        let mut order = Vec::new();
+
+        for root in 0..self.len() {
+            if states[root] != UNVISITED {
+                continue;
+            }
+            states[root] = ON_PATH;
+            path.push((root, 0));
+            while let Some((idx, explored)) = path.last_mut() {
+                let idx = *idx;
+                match self.successors(idx).get(*explored) {
+                    Some(&succ) => {
+                        *explored += 1;
+                        match states[succ] {
+                            UNVISITED => {
+                                states[succ] = ON_PATH;
+                                path.push((succ, 0));
+                            }
+                            ON_PATH => {
+                                // back edge: the path from `succ` to `idx` plus this edge is a cycle
+                                let start = path.iter().position(|&(n, _)| n == succ).unwrap_or(0);
+                                let cycle =
+                                    path[start..].iter().map(|&(n, _)| self.node(n)).collect();
+                                return Err(DynError::Cycle(cycle));
+                            }
+                            _ => {}
+                        }
+                    }
+                    None => {
+                        states[idx] = DONE;
+                        path.pop();
+                        order.push(idx);
+                    }
+                }
+            }
+        }
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+
+    #[test]
+    fn ok() {
+        let graph: DynGraph<usize> = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)]
+            .into_iter()
+            .map(Edge::from)
+            .collect();
+        assert_eq!(Ok(vec![1, 2, 3, 4, 5]), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn same_order_as_graph() {
+        let edge_data = [(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2), (6, 6)];
+        let mut graph = Graph::<usize, 8>::new();
+        let mut dyn_graph = DynGraph::new();
+        for add in [7, 6] {
+            graph.add_node(add).unwrap();
+            dyn_graph.add_node(add);
+        }
+        for edge in edge_data.into_iter().take(6).map(Edge::from) {
+            graph.insert_edge(edge).unwrap();
+            dyn_graph.insert_edge(edge);
+        }
+        let expected = graph.clone().into_topo_sorted().unwrap();
+        assert_eq!(expected.as_slice(), dyn_graph.topo_sorted().unwrap());
+
+        let edge = Edge::from(edge_data[6]);
+        graph.insert_edge(edge).unwrap();
+        dyn_graph.insert_edge(edge);
+        let Err(crate::Error::Cycle(expected)) = graph.into_topo_sorted() else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(
+            Err(DynError::Cycle(expected.to_vec())),
+            dyn_graph.into_topo_sorted()
+        );
+    }
+
+    #[test]
+    fn variants_match_graph() {
+        let edge_data = [(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2), (1, 6)];
+        let mut graph = Graph::<usize, 8>::from_array(edge_data);
+        let mut dyn_graph: DynGraph<usize> = edge_data.into_iter().map(Edge::from).collect();
+        graph.add_node(9).unwrap();
+        dyn_graph.add_node(9);
+
+        assert!(graph.roots().eq(dyn_graph.roots()));
+        assert!(graph.leaves().eq(dyn_graph.leaves()));
+        for node in [0, 1, 4, 9, 42] {
+            assert_eq!(graph.in_degree(node), dyn_graph.in_degree(node));
+            assert_eq!(graph.out_degree(node), dyn_graph.out_degree(node));
+        }
+        assert!(dyn_graph.is_dag());
+        assert_eq!(None, dyn_graph.find_cycle());
+
+        let mut out = vec![42];
+        dyn_graph.topo_sort_into(&mut out).unwrap();
+        assert_eq!(graph.topo_sorted().unwrap().as_slice(), out);
+        let rev = graph.clone().into_topo_sorted_rev().unwrap();
+        assert_eq!(
+            rev.as_slice(),
+            dyn_graph.clone().into_topo_sorted_rev().unwrap()
+        );
+        let lex = graph.clone().into_topo_sorted_lex().unwrap();
+        assert_eq!(
+            lex.as_slice(),
+            dyn_graph.clone().into_topo_sorted_lex().unwrap()
+        );
+        let stable = graph.clone().into_topo_sorted_stable().unwrap();
+        assert_eq!(
+            stable.as_slice(),
+            dyn_graph.clone().into_topo_sorted_stable().unwrap()
+        );
+        let by = graph.clone().into_topo_sorted_by(|a, b| b.cmp(&a)).unwrap();
+        assert_eq!(
+            by.as_slice(),
+            dyn_graph
+                .clone()
+                .into_topo_sorted_by(|a, b| b.cmp(&a))
+                .unwrap()
+        );
+        let dfs = graph.clone().into_topo_sorted_dfs().unwrap();
+        assert_eq!(
+            dfs.as_slice(),
+            dyn_graph.clone().into_topo_sorted_dfs().unwrap()
+        );
+        let levels = graph.into_levels().unwrap();
+        let dyn_levels = dyn_graph.into_levels().unwrap();
+        assert!(levels
+            .iter()
+            .map(|level| level.as_slice())
+            .eq(dyn_levels.iter().map(Vec::as_slice)));
+    }
+
+    #[test]
+    fn hash_only_nodes() {
+        // `Hash` but not `Ord`, like `Graph` accepts
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        struct Task(u8);
+
+        let mut graph = DynGraph::new();
+        graph.insert_edge(Edge::new(Task(2), Task(1)));
+        graph.insert_edge(Edge::new(Task(1), Task(0)));
+        assert_eq!(vec![Task(2)], graph.roots());
+        assert_eq!(Ok(vec![Task(2), Task(1), Task(0)]), graph.topo_sorted());
+        graph.insert_edge(Edge::new(Task(0), Task(2)));
+        assert_eq!(Some(vec![Task(1), Task(0), Task(2)]), graph.find_cycle());
+        assert!(matches!(
+            graph.into_topo_sorted_dfs(),
+            Err(DynError::Cycle(_))
+        ));
+    }
+
+    #[test]
+    fn large() {
+        // far beyond anything a stack-allocated graph could hold
+        let mut graph = DynGraph::with_capacity(10_000);
+        for node in (0..10_000u32).rev() {
+            graph.insert_edge(Edge::new(node, node + 1));
+        }
+        let sorted = graph.into_topo_sorted().unwrap();
+        assert_eq!((0..=10_000).collect::<Vec<_>>(), sorted);
+    }
+
+    #[test]
+    fn err_cycle() {
+        let mut graph = DynGraph::new();
+        graph.extend([(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)].map(Edge::from));
+        assert_eq!(Err(DynError::Cycle(vec![2, 3, 1])), graph.topo_sorted());
+        assert!(graph.remove_edge(Edge::new(3, 1)));
+        assert_eq!(Ok(vec![0, 1, 2, 3, 4]), graph.into_topo_sorted());
+    }
+}
@@ -0,0 +1,148 @@
+//! Sorting without a hash table, see [`Graph::into_topo_sorted_hash_free`].
+
+use core::hash::Hash;
+
+use heapless::Vec;
+
+use crate::{adjacency::fill_csr, find_cycle, Error, Graph};
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Graph<N, EDGES, NODES> {
+    /// compute the lexicographically smallest topological sort without hashing, consuming self.
+    ///
+    /// Produces the same order as [`Graph::into_topo_sorted_lex`], but never builds a hash table: the distinct
+    /// nodes are kept in a sorted `Vec` and looked up by binary search, and the ready nodes live in another sorted
+    /// `Vec` instead of a binary heap. That makes the temporary memory exactly one `N` and four `usize`s per node
+    /// plus one `usize` per edge, with no slack for the hash table, at the cost of `O(nodes²)` element moves for
+    /// the sorted inserts. On small graphs that cost is negligible.
+    ///
+    /// Returns `Error::OverCapacity` if there are more than `NODES` distinct nodes, and `Error::Cycle` for cyclic
+    /// graphs.
+    ///
+    /// ```
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 4>::from_array([(9, 2), (5, 2), (7, 9)]);
+    /// let sorted = graph.into_topo_sorted_hash_free().ok().unwrap();
+    /// assert_eq!(&[5, 7, 9, 2], sorted.as_slice());
+    /// ```
+    pub fn into_topo_sorted_hash_free(self) -> Result<Vec<N, NODES>, Error<N, EDGES>>
+    where
+        N: Ord,
+    {
+        // distinct nodes in ascending order, so a dense index compares like its node id
+        let mut nodes: Vec<N, NODES> = Vec::new();
+        let endpoints = self.edges.iter().flat_map(|edge| [edge.from, edge.to]);
+        for node in self.nodes.iter().copied().chain(endpoints) {
+            if let Err(pos) = nodes.binary_search(&node) {
+                nodes
+                    .insert(pos, node)
+                    .map_err(|_| Error::nodes_over_capacity(&self.nodes, &self.edges))?;
+            }
+        }
+        // unwrap safety: every endpoint was inserted above
+        let index = |node: N| nodes.binary_search(&node).unwrap();
+
+        let mut starts: Vec<usize, NODES> = Vec::new();
+        let mut in_degrees: Vec<usize, NODES> = Vec::new();
+        let mut targets: Vec<usize, EDGES> = Vec::new();
+        // can't fail: one entry per node or edge
+        let _ = starts.resize(nodes.len(), 0);
+        let _ = in_degrees.resize(nodes.len(), 0);
+        let _ = targets.resize(self.edges.len(), 0);
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| (index(edge.from), index(edge.to)));
+        fill_csr(edges, &mut starts, &mut targets, &mut in_degrees);
+
+        // ready nodes in descending order, so the smallest one pops off the end
+        let mut ready: Vec<usize, NODES> = (0..nodes.len())
+            .rev()
+            .filter(|&idx| in_degrees[idx] == 0)
+            .collect();
+        let mut order: Vec<usize, NODES> = Vec::new();
+        while let Some(idx) = ready.pop() {
+            // can't fail: every node is sorted at most once
+            let _ = order.push(idx);
+            let end = starts.get(idx + 1).copied().unwrap_or(targets.len());
+            for &succ in &targets[starts[idx]..end] {
+                in_degrees[succ] -= 1;
+                if in_degrees[succ] == 0 {
+                    // `succ` isn't in `ready` yet, so the search always ends at its insert position
+                    let pos = ready
+                        .binary_search_by(|probe| succ.cmp(probe))
+                        .unwrap_or_else(|pos| pos);
+                    // can't fail: every node is ready at most once
+                    let _ = ready.insert(pos, succ);
+                }
+            }
+        }
+        if order.len() < nodes.len() {
+            // the unsorted nodes are exactly those with edges left pointing to them
+            let residual = self
+                .edges
+                .iter()
+                .filter(|edge| in_degrees[index(edge.from)] != 0);
+            return Err(Error::Cycle(find_cycle(residual)?));
+        }
+        Ok(order.iter().map(|&idx| nodes[idx]).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_lex() {
+        let edge_data = [(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2)];
+        let mut graph = Graph::<usize, 8>::from_array(edge_data);
+        graph.add_node(9).unwrap();
+        graph.add_node(4).unwrap();
+        let sorted = graph.clone().into_topo_sorted_hash_free().unwrap();
+        assert_eq!(&[4, 5, 0, 2, 3, 1, 9], sorted.as_slice());
+        assert_eq!(graph.into_topo_sorted_lex().unwrap(), sorted);
+
+        // independent of edge order
+        let mut reversed = edge_data;
+        reversed.reverse();
+        let graph = Graph::<usize, 8>::from_array(reversed);
+        assert_eq!(
+            &[4, 5, 0, 2, 3, 1],
+            graph.into_topo_sorted_hash_free().unwrap().as_slice()
+        );
+
+        assert!(Graph::<usize, 8>::new()
+            .into_topo_sorted_hash_free()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn err_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)]);
+        let expected = [2, 3, 1].as_slice().try_into().unwrap();
+        assert_eq!(
+            Err(Error::Cycle(expected)),
+            graph.clone().into_topo_sorted_hash_free()
+        );
+        assert_eq!(
+            graph.clone().into_topo_sorted_lex(),
+            graph.into_topo_sorted_hash_free()
+        );
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let graph = Graph::<usize, 4, 3>::from_array([(0, 1), (1, 2), (2, 3), (0, 3)]);
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 4 }),
+            graph.into_topo_sorted_hash_free()
+        );
+        // exactly full works
+        let graph = Graph::<usize, 4, 3>::from_array([(2, 1), (1, 0)]);
+        assert_eq!(
+            &[2, 1, 0],
+            graph.into_topo_sorted_hash_free().unwrap().as_slice()
+        );
+    }
+}
@@ -4,11 +4,36 @@
 //! This crate assumes your main graph data is stored elsewhere and you only create a `Graph` temporarily
 //! with the express purpose of doing toposort. Hence the design decisions:
 //! - only store edge information, no node payload
-//! - consume `self` on sort.
+//! - consume `self` on sort (use `topo_sorted` instead if you re-sort the same graph after small edits).
 //!
 //! # Capacity requirements
-//! The implementation uses one single const generic for all temporary data structures. In the pathological case
-//! it requires (number of graph edges) + 1, so be mindful of that: a toposort of e.g. `[(0,1), (1,2)]` is `[0,1,2]`.
+//! `Graph<N, EDGES, NODES>` uses two const generics:
+//! - `EDGES` bounds the number of stored edges (and the temporary per-edge data structures)
+//! - `NODES` bounds the number of distinct nodes, which sizes the temporary per-node data structures
+//!   (starting nodes, in-degree counts) and the sorted output.
+//!   It defaults to `EDGES`, so `Graph<N, EDGES>` is enough for most uses.
+//!
+//! A sorted graph with `n` edges may contain up to `n + 1` nodes (a toposort of e.g. `[(0,1), (1,2)]` is `[0,1,2]`),
+//! so in the pathological case `NODES` needs to be `EDGES + 1`. Nodes registered via `add_node` count towards
+//! `NODES` as well. Neither needs to be a power of two. Use [`Graph::required_capacity`] to size both for a given
+//! graph.
+//!
+//! ## Temporary memory
+//! Sorting maps node ids to dense indices `0..n` once, through a hash table of four bytes per node (`2 * NODES`
+//! slots of a `u16` each, linear probing, no power-of-two rounding). Keeping it at most half full makes each lookup
+//! `O(1)` expected time, so sorting stays linear in expectation. Beyond 65534 nodes the slots need 32 bits, so
+//! there are only `NODES` of them and lookups slow down as the table fills up. Everything after that works on
+//! plain `Vec`s indexed by dense index: the ready nodes are a FIFO queue in a `Vec<usize, NODES>` rather than a
+//! hash set, and the RAM use is fixed by `EDGES` and `NODES` alone.
+//!
+//! For `N: Ord`, [`Graph::into_topo_sorted_hash_free`] avoids hashing altogether: it keeps the distinct nodes and
+//! the ready set in sorted `Vec`s with binary-search lookup and insert. That costs `O(n)` element moves per insert,
+//! negligible on small graphs, and yields the smallest-first order of [`Graph::into_topo_sorted_lex`] for free.
+//! `into_topo_sorted_lex` itself gets that order from a binary heap in `O(log n)` per node, but hashes like the
+//! other sorts.
+//!
+//! If your node ids are small and dense, [`GraphDense`] replaces all per-node tables with bitsets, at the cost of
+//! rescanning the edges once per level.
 //!
 //! # Usage
 //!
@@ -16,46 +41,192 @@
 //! use heapless_topo::{Graph, Edge};
 //! const CAPACITY: usize = 8;
 //! // or `new_with_edges` if you have a `Vec<Edge>` already
-//! let mut graph = Graph::<CAPACITY>::new();
-//! graph.insert_edge(Edge::from((1,2)));
+//! let mut graph = Graph::<usize, CAPACITY>::new();
+//! let edge = Edge::from((1,2));
+//! assert_eq!((1, 2), (edge.source(), edge.target()));
+//! graph.insert_edge(edge);
 //! graph.insert_edge(Edge::from((0,1)));
+//! assert_eq!(2, graph.edge_count());
+//! assert!(!graph.is_empty());
 //! let sorted = graph.into_topo_sorted();
 //! let expected = [0,1,2].as_slice().try_into().unwrap();
-//! assert_eq!(Ok(expected), sorted);
+//! assert!(sorted == Ok(expected));
 //! ```
 //!
+//! # Ordering
+//! A graph usually has many valid topological sorts. The one returned by `into_topo_sorted` is deterministic:
+//! it only depends on the order in which nodes and edges were inserted, never on hashing or the platform, so
+//! the same graph built the same way sorts the same everywhere. Concretely, nodes are emitted breadth-first:
+//! first all starting nodes in order of first appearance, then every other node in the order it becomes ready.
+//!
+//! If you need an order that doesn't depend on insertion order either, use `into_topo_sorted_lex`.
+//!
+//! # Node ids
+//! Nodes are identified by any `N: Copy + Eq + Hash`, e.g. `usize`, a smaller integer or a fieldless enum,
+//! so there is no need to translate your own node ids to and from `usize`.
+//!
+//! On small targets, narrow integer ids save RAM where ids are stored: the edges, the nodes added via
+//! [`Graph::add_node`], and the table mapping dense indices back to ids while sorting. E.g. a `Graph<u16, EDGES>`
+//! needs half the edge storage of a `Graph<usize, EDGES>` on a 32-bit MCU. The other temporary per-node tables
+//! (in-degrees, edge offsets, the ready queue) hold dense indices or counts, so their size doesn't depend on `N`.
+//!
 //! # Crate features
-//! - `std` for `#[derive(Debug)]`
+//! - `std` for `#[derive(Debug)]` (`Error` and `DynError` implement `Debug` and `core::error::Error` regardless),
+//!   and `Graph::to_dot`. Implies `alloc`.
+//! - `alloc` for `DynGraph`, a growable graph for when the size is only known at runtime
 //! - `defmt-03` for `#[derive(Format)]` using `defmt` v0.3
+//! - `serde` for `Serialize`/`Deserialize` of `Edge` (as `[from, to]`) and `Graph`. Deserializing more
+//!   than `EDGES` edges (or `NODES` added nodes) is an error.
+//! - `petgraph` for converting from and to `petgraph::Graph` via `Graph::from_petgraph` and `Graph::to_petgraph`
 //!
 
-use heapless::{FnvIndexSet, Vec};
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+use core::{cmp::Ordering, hash::Hash};
+
+use heapless::Vec;
+
+mod adjacency;
+use adjacency::Adjacency;
+mod buffer;
+use buffer::Buffer;
+#[cfg(feature = "alloc")]
+mod dyn_graph;
+#[cfg(feature = "alloc")]
+pub use dyn_graph::{DynError, DynGraph};
+mod compact;
+pub use compact::{Compacted, Mapping};
+mod components;
+pub use components::{Components, Condensation};
+mod dense;
+pub use dense::GraphDense;
+mod dot;
+mod hash_free;
+mod matrix;
+mod node_index;
+use node_index::NodeIndex;
+mod node_map;
+pub use node_map::NodeMap;
+mod ordered;
+#[cfg(feature = "petgraph")]
+mod petgraph_compat;
+pub use ordered::OrderedGraph;
+mod scratch;
+pub use scratch::Scratch;
+mod topo_iter;
+pub use topo_iter::TopoIter;
+mod weighted;
+pub use weighted::WeightedGraph;
+
+/// Why a graph operation failed.
+///
+/// Implements `Debug`, `Display` and `core::error::Error` even without the `std` feature, so it works with `?` and
+/// error-reporting code on `no_std` as well:
+///
+/// ```
+/// # use heapless_topo::Graph;
+/// let graph = Graph::<u8, 4>::from_array([(0, 1), (1, 0)]);
+/// let err = graph.into_topo_sorted().unwrap_err();
+/// let err: &dyn core::error::Error = &err;
+/// assert_eq!("cycle detected", format!("{err}"));
+/// ```
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
-#[derive(PartialEq, Eq)]
-pub enum Error {
-    Cycle,
-    OverCapacity,
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Error<N, const EDGES: usize> {
+    /// The graph contains a cycle. The payload lists the nodes of one such cycle in edge order,
+    /// i.e. each node has an edge to the next one and the last node has an edge back to the first.
+    Cycle(Vec<N, EDGES>),
+    /// A fixed-capacity container was too small. `needed` is the capacity that would have been required, or a lower
+    /// bound if the exact number isn't known without extra memory (e.g. `capacity + 1` for an output buffer).
+    OverCapacity { needed: usize },
+    /// Inserting the edge would have created a cycle, see [`Graph::try_insert_edge`].
+    WouldCycle,
+    /// The edge is a self-loop, i.e. a cycle of length one, see [`Graph::try_insert_edge`].
+    SelfLoop,
+    /// An equal edge is already present, see [`Graph::insert_edge_strict`].
+    DuplicateEdge,
+}
+
+impl<N, const EDGES: usize> core::fmt::Display for Error<N, EDGES> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Cycle(_) => f.write_str("cycle detected"),
+            Error::OverCapacity { needed } => {
+                write!(f, "capacity exceeded, needs at least {needed}")
+            }
+            Error::WouldCycle => f.write_str("edge would create a cycle"),
+            Error::SelfLoop => f.write_str("edge is a self-loop"),
+            Error::DuplicateEdge => f.write_str("duplicate edge"),
+        }
+    }
+}
+
+impl<N: Copy + Eq, const EDGES: usize> Error<N, EDGES> {
+    /// `OverCapacity` for a graph whose distinct nodes don't fit, counting them without extra memory.
+    pub(crate) fn nodes_over_capacity(nodes: &[N], edges: &[Edge<N>]) -> Self {
+        Error::OverCapacity {
+            needed: count_distinct_nodes(nodes, edges),
+        }
+    }
+}
+
+/// Count the distinct nodes among added nodes and edge endpoints without extra memory, in `O((nodes + edges)²)`.
+/// Meant for when they don't fit a set of capacity `NODES`, so performance doesn't matter much.
+fn count_distinct_nodes<N: Copy + Eq>(nodes: &[N], edges: &[Edge<N>]) -> usize {
+    let all = nodes
+        .iter()
+        .copied()
+        .chain(edges.iter().flat_map(|edge| [edge.from, edge.to]));
+    all.clone()
+        .enumerate()
+        .filter(|&(i, node)| !all.clone().take(i).any(|n| n == node))
+        .count()
 }
 
+impl<N: core::fmt::Debug, const EDGES: usize> core::error::Error for Error<N, EDGES> {}
+
 /// Graph edge
+///
+/// Edges are ordered lexicographically by `(from, to)`, so a list of edges can simply be `sort()`ed,
+/// e.g. for deterministic output.
 #[cfg_attr(any(test, feature = "std"), derive(Debug))]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
-#[derive(PartialEq, Eq, Clone, Copy)]
-pub struct Edge {
-    pub from: usize,
-    pub to: usize,
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Edge<N> {
+    pub from: N,
+    pub to: N,
 }
 
-impl Edge {
-    pub fn new(from: usize, to: usize) -> Self {
+impl<N> Edge<N> {
+    pub fn new(from: N, to: N) -> Self {
         Self { from, to }
     }
+
+    /// The same edge pointing the other way, i.e. with `from` and `to` swapped.
+    pub fn reversed(self) -> Self {
+        Self {
+            from: self.to,
+            to: self.from,
+        }
+    }
+}
+
+impl<N: Copy> Edge<N> {
+    /// The node this edge starts at (`from`).
+    /// Named `source` rather than `from` so it doesn't shadow `Edge::from`.
+    pub fn source(&self) -> N {
+        self.from
+    }
+
+    /// The node this edge points to (`to`).
+    pub fn target(&self) -> N {
+        self.to
+    }
 }
 
-impl From<(usize, usize)> for Edge {
-    fn from(value: (usize, usize)) -> Self {
+impl<N> From<(N, N)> for Edge<N> {
+    fn from(value: (N, N)) -> Self {
         Self {
             from: value.0,
             to: value.1,
@@ -63,188 +234,3160 @@ impl From<(usize, usize)> for Edge {
     }
 }
 
+/// Serialized as a compact `[from, to]` pair.
+#[cfg(feature = "serde")]
+impl<N: serde::Serialize> serde::Serialize for Edge<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.from, &self.to).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, N: serde::Deserialize<'de>> serde::Deserialize<'de> for Edge<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <(N, N)>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Result of [`Graph::into_topo_sorted_partial`]: the sorted prefix and the residual (unsortable) edges.
+pub type PartialSort<N, const EDGES: usize, const NODES: usize> =
+    (Vec<N, NODES>, Vec<Edge<N>, EDGES>);
+
+/// Result of [`Graph::into_levels`]: the nodes grouped by level.
+pub type Levels<N, const NODES: usize> = Vec<Vec<N, NODES>, NODES>;
+
+/// Result of [`Graph::into_frontiers`]: the ready nodes before each step of the sort.
+pub type Frontiers<N, const NODES: usize> = Vec<Vec<N, NODES>, NODES>;
+
 /// payload-agnostic Graph (pure edge data)
+///
+/// `PartialEq` compares the stored edges and added nodes exactly, including their order. Use
+/// [`Graph::eq_unordered`] to check whether two graphs have the same edges regardless of insertion order, or
+/// [`Graph::canonicalize`] both first.
 #[cfg_attr(any(test, feature = "std"), derive(Debug))]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
-#[derive(Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub struct Graph<N, const EDGES: usize, const NODES: usize = EDGES> {
+    edges: Vec<Edge<N>, EDGES>,
+    // explicitly added nodes, see `add_node`
+    nodes: Vec<N, NODES>,
+}
 
-pub struct Graph<const CAP: usize> {
-    edges: Vec<Edge, CAP>,
+impl<N, const EDGES: usize, const NODES: usize> Default for Graph<N, EDGES, NODES> {
+    fn default() -> Self {
+        Self {
+            edges: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
 }
 
-impl<const CAP: usize> Graph<CAP> {
-    /// Create a new, empty graph.
-    pub fn new() -> Self {
-        Self { edges: Vec::new() }
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Graph<N, EDGES, NODES> {
+    /// Create a new, empty graph. Being `const`, this can initialize a `static` or `const` graph.
+    pub const fn new() -> Self {
+        Self {
+            edges: Vec::new(),
+            nodes: Vec::new(),
+        }
     }
 
     /// Create a new graph from existing edge data
-    pub fn new_with_edges(edges: Vec<Edge, CAP>) -> Self {
-        Self { edges }
+    pub fn new_with_edges(edges: Vec<Edge<N>, EDGES>) -> Self {
+        Self {
+            edges,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Create a new graph from an array of `(from, to)` pairs.
+    /// Arrays longer than `EDGES` are rejected at compile time, so this can't fail at runtime.
+    ///
+    /// ```compile_fail
+    /// use heapless_topo::Graph;
+    /// let graph = Graph::<usize, 1>::from_array([(1, 2), (2, 3)]);
+    /// ```
+    pub fn from_array<const LEN: usize>(edges: [(N, N); LEN]) -> Self {
+        const { assert!(LEN <= EDGES, "array has more edges than the graph capacity") };
+        let mut graph = Self::new();
+        for edge in edges {
+            // can't fail: LEN <= EDGES
+            let _ = graph.insert_edge(edge.into());
+        }
+        graph
+    }
+
+    /// Create a new graph from an iterator of edges or `(from, to)` tuples.
+    /// Returns `Error::OverCapacity` as soon as the iterator yields more than `EDGES` items.
+    pub fn try_from_iter<E: Into<Edge<N>>, I: IntoIterator<Item = E>>(
+        iter: I,
+    ) -> Result<Self, Error<N, EDGES>> {
+        let mut graph = Self::new();
+        graph.try_extend(iter)?;
+        Ok(graph)
+    }
+
+    /// Append edges or `(from, to)` tuples from an iterator.
+    /// Same as [`Graph::insert_edges`], including the partial insert on failure.
+    pub fn try_extend<E: Into<Edge<N>>, I: IntoIterator<Item = E>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), Error<N, EDGES>> {
+        self.insert_edges(iter.into_iter().map(Into::into))
+    }
+
+    /// Register a node, so it is part of the sort even if no edge references it.
+    /// Adding a node twice has no effect. Nodes that occur in edges don't need to be added.
+    /// Returns `Error::OverCapacity` if more than `NODES` nodes have been added.
+    pub fn add_node(&mut self, node: N) -> Result<(), Error<N, EDGES>> {
+        if self.nodes.contains(&node) {
+            return Ok(());
+        }
+        self.nodes
+            .push(node)
+            .map_err(|_| Error::OverCapacity { needed: NODES + 1 })
     }
 
     /// Insert an edge into the graph. No duplicate check is performed.
     /// Returns `Error::OverCapacity` if full.
-    pub fn insert_edge(&mut self, edge: Edge) -> Result<(), Error> {
-        self.edges.push(edge).map_err(|_| Error::OverCapacity)
+    pub fn insert_edge(&mut self, edge: Edge<N>) -> Result<(), Error<N, EDGES>> {
+        self.edges
+            .push(edge)
+            .map_err(|_| Error::OverCapacity { needed: EDGES + 1 })
     }
 
-    /// compute topological sort, consuming self.
-    pub fn into_topo_sorted(self) -> Result<Vec<usize, CAP>, Error> {
-        let mut edges = self.edges;
+    /// Like [`Graph::insert_edge`], but returns the position of the new edge in [`Graph::edges`], e.g. to key a
+    /// side table. Positions are sequential; they stay valid until an edge before them is removed.
+    pub fn insert_edge_at(&mut self, edge: Edge<N>) -> Result<usize, Error<N, EDGES>> {
+        let idx = self.edges.len();
+        self.insert_edge(edge)?;
+        Ok(idx)
+    }
 
-        let mut topo_sorted = Vec::new();
-        // compute a list of starting nodes, i.e. nodes with no incoming edges
-        //
-        // reuse CAP size here to prevent generics clutter.
-        // nb: in dense graphs this is wasteful.
-        let mut starting_nodes: FnvIndexSet<usize, CAP> = FnvIndexSet::new();
-
-        // first assume all edges connect from a starting node
-        for edge in &edges {
-            starting_nodes
-                .insert(edge.from)
-                .map_err(|_| Error::OverCapacity)?;
-        }
-
-        // now remove all nodes that do have an incoming edge
-        for edge in &edges {
-            if starting_nodes.contains(&edge.to) {
-                starting_nodes.remove(&edge.to);
+    /// Insert an edge unless that would create a cycle, see [`Graph::would_create_cycle`].
+    ///
+    /// Returns `Error::OverCapacity` if full, `Error::SelfLoop` for an edge from a node to itself, and
+    /// `Error::WouldCycle` if the edge would close a longer cycle; the graph is unchanged in all cases. A graph built
+    /// only with this method is acyclic by construction, so sorting it never fails with `Error::Cycle`. Takes
+    /// `O(edges²)` time, like [`Graph::reachable`].
+    pub fn try_insert_edge(&mut self, edge: Edge<N>) -> Result<(), Error<N, EDGES>> {
+        if self.remaining_capacity() == 0 {
+            return Err(Error::OverCapacity { needed: EDGES + 1 });
+        }
+        if edge.from == edge.to {
+            return Err(Error::SelfLoop);
+        }
+        if self.would_create_cycle(edge) {
+            return Err(Error::WouldCycle);
+        }
+        self.insert_edge(edge)
+    }
+
+    /// Insert all edges from an iterator. No duplicate check is performed.
+    ///
+    /// Returns `Error::OverCapacity` on the first edge that doesn't fit. Note that this is *not* atomic:
+    /// edges inserted before the failing one stay in the graph, and the rest of the iterator is not consumed.
+    /// Check [`Graph::remaining_capacity`] up front if you need all-or-nothing behavior.
+    pub fn insert_edges<I: IntoIterator<Item = Edge<N>>>(
+        &mut self,
+        edges: I,
+    ) -> Result<(), Error<N, EDGES>> {
+        for edge in edges {
+            self.insert_edge(edge)?;
+        }
+        Ok(())
+    }
+
+    /// Insert an edge unless an equal edge is already present.
+    /// Returns `Ok(true)` if the edge was inserted, `Ok(false)` if it was a duplicate,
+    /// and `Error::OverCapacity` only if a new edge doesn't fit.
+    pub fn insert_edge_unique(&mut self, edge: Edge<N>) -> Result<bool, Error<N, EDGES>> {
+        if self.contains_edge(edge) {
+            return Ok(false);
+        }
+        self.insert_edge(edge)?;
+        Ok(true)
+    }
+
+    /// Append all edges and added nodes of `other`, e.g. to combine independently assembled sub-graphs.
+    /// No duplicate check is performed on edges, see [`Graph::merge_unique`].
+    ///
+    /// Returns `Error::OverCapacity` if the combined edges or added nodes don't fit, in which case self is left
+    /// unchanged.
+    pub fn merge<const OTHER_EDGES: usize, const OTHER_NODES: usize>(
+        &mut self,
+        other: &Graph<N, OTHER_EDGES, OTHER_NODES>,
+    ) -> Result<(), Error<N, EDGES>> {
+        self.check_merge_capacity(other.edges.len(), other)?;
+        let needed = self.edges.len() + other.edges.len();
+        // can't fail: checked above
+        self.edges
+            .extend_from_slice(&other.edges)
+            .map_err(|_| Error::OverCapacity { needed })?;
+        self.merge_nodes(other)
+    }
+
+    /// Like [`Graph::merge`], but skips edges of `other` that are already present (or occur in `other` twice).
+    /// Returns the number of edges inserted.
+    pub fn merge_unique<const OTHER_EDGES: usize, const OTHER_NODES: usize>(
+        &mut self,
+        other: &Graph<N, OTHER_EDGES, OTHER_NODES>,
+    ) -> Result<usize, Error<N, EDGES>> {
+        let new_edges = other
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|&(idx, edge)| !self.edges.contains(edge) && !other.edges[..idx].contains(edge))
+            .count();
+        self.check_merge_capacity(new_edges, other)?;
+        for &edge in &other.edges {
+            // can't fail: checked above
+            self.insert_edge_unique(edge)?;
+        }
+        self.merge_nodes(other)?;
+        Ok(new_edges)
+    }
+
+    /// Make sure `new_edges` more edges plus the added nodes of `other` fit.
+    fn check_merge_capacity<const OTHER_EDGES: usize, const OTHER_NODES: usize>(
+        &self,
+        new_edges: usize,
+        other: &Graph<N, OTHER_EDGES, OTHER_NODES>,
+    ) -> Result<(), Error<N, EDGES>> {
+        let new_nodes = other
+            .nodes
+            .iter()
+            .filter(|node| !self.nodes.contains(node))
+            .count();
+        if new_edges > self.remaining_capacity() {
+            let needed = self.edges.len() + new_edges;
+            return Err(Error::OverCapacity { needed });
+        }
+        if self.nodes.len() + new_nodes > NODES {
+            let needed = self.nodes.len() + new_nodes;
+            return Err(Error::OverCapacity { needed });
+        }
+        Ok(())
+    }
+
+    fn merge_nodes<const OTHER_EDGES: usize, const OTHER_NODES: usize>(
+        &mut self,
+        other: &Graph<N, OTHER_EDGES, OTHER_NODES>,
+    ) -> Result<(), Error<N, EDGES>> {
+        for &node in &other.nodes {
+            self.add_node(node)?;
+        }
+        Ok(())
+    }
+
+    /// Insert an edge, rejecting duplicates with `Error::DuplicateEdge` rather than ignoring them like
+    /// [`Graph::insert_edge_unique`] does. Use this to guarantee a graph without parallel edges.
+    /// Returns `Error::OverCapacity` if a new edge doesn't fit.
+    pub fn insert_edge_strict(&mut self, edge: Edge<N>) -> Result<(), Error<N, EDGES>> {
+        if self.contains_edge(edge) {
+            return Err(Error::DuplicateEdge);
+        }
+        self.insert_edge(edge)
+    }
+
+    /// Check whether an edge equal to `edge` is present.
+    pub fn contains_edge(&self, edge: Edge<N>) -> bool {
+        self.edges.contains(&edge)
+    }
+
+    /// Whether both graphs have the same edges and added nodes, regardless of order.
+    ///
+    /// Edges are compared as multisets, so duplicates have to match up as well. Unlike `==`, this doesn't care
+    /// about insertion order. Takes `O(edges²)` time.
+    pub fn eq_unordered(&self, other: &Self) -> bool {
+        let count = |edges: &[Edge<N>], edge: &Edge<N>| edges.iter().filter(|e| *e == edge).count();
+        self.edges.len() == other.edges.len()
+            && self.nodes.len() == other.nodes.len()
+            && self
+                .edges
+                .iter()
+                .all(|edge| count(&self.edges, edge) == count(&other.edges, edge))
+            // add_node dedups, so equal lengths plus containment means equal sets
+            && self.nodes.iter().all(|node| other.nodes.contains(node))
+    }
+
+    /// Relabel every node via `f`, consuming self. Edge order is kept.
+    ///
+    /// The node type may change, e.g. to narrow ids. Note that if `f` maps distinct nodes to the same id, their
+    /// edges merge: this can create duplicate edges, self-loops, and cycles. Collapsed added nodes are deduplicated.
+    pub fn map_nodes<M: Copy + Eq + Hash>(
+        self,
+        mut f: impl FnMut(N) -> M,
+    ) -> Graph<M, EDGES, NODES> {
+        let mut mapped = Graph::new();
+        for edge in self.edges {
+            // can't fail: there are as many edges as in self
+            let _ = mapped.insert_edge(Edge::new(f(edge.from), f(edge.to)));
+        }
+        for node in self.nodes {
+            // can't fail: there are at most as many added nodes as in self
+            let _ = mapped.add_node(f(node));
+        }
+        mapped
+    }
+
+    /// Bring the graph into a normal form by sorting the edges (see [`Edge`]'s `Ord`) and the added nodes.
+    ///
+    /// Afterwards, graphs with the same edges and added nodes compare equal with `==` and have the same `Hash`,
+    /// regardless of the order they were built in, so canonical graphs can be used as cache keys. Duplicate edges
+    /// are kept. Note that this changes the order of first appearance and thus the result of
+    /// [`Graph::into_topo_sorted`] (see the crate docs on ordering).
+    pub fn canonicalize(&mut self)
+    where
+        N: Ord,
+    {
+        self.edges.sort_unstable();
+        self.nodes.sort_unstable();
+    }
+
+    /// Remove `node` entirely: every edge from or to it, and its registration via [`Graph::add_node`].
+    /// The remaining edges keep their order. Neighbors that were only referenced by the removed edges are no longer
+    /// part of the graph either, unless they were added via [`Graph::add_node`]. Returns the number of edges removed.
+    pub fn remove_node(&mut self, node: N) -> usize {
+        let before = self.edges.len();
+        self.edges
+            .retain(|edge| edge.from != node && edge.to != node);
+        self.nodes.retain(|&n| n != node);
+        before - self.edges.len()
+    }
+
+    /// Keep only the edges for which `keep` returns `true`, in their original order, e.g. to drop all edges of a
+    /// disabled feature before sorting. Nodes added via [`Graph::add_node`] are unaffected.
+    pub fn retain_edges<F: FnMut(&Edge<N>) -> bool>(&mut self, keep: F) {
+        self.edges.retain(keep);
+    }
+
+    /// Remove the first edge equal to `edge`, keeping the order of the remaining edges.
+    /// Returns whether an edge was removed.
+    pub fn remove_edge(&mut self, edge: Edge<N>) -> bool {
+        match self.edges.iter().position(|e| *e == edge) {
+            Some(idx) => {
+                self.edges.remove(idx);
+                true
             }
+            None => false,
         }
+    }
 
-        // Kahn's algorithm
-        // https://en.wikipedia.org/wiki/Topological_sorting#Kahn's_algorithm
-        // L (here: `res`) ← Empty list that will contain the sorted elements
-        // S (here: `starting_nodes`) ← Set of all nodes with no incoming edge
-
-        while !starting_nodes.is_empty() {
-            // 1. remove a node n from S
-            // unwrap safety: we just checked !is_empty
-            let node = *starting_nodes.first().unwrap();
-            starting_nodes.remove(&node);
-
-            // add N to L
-            topo_sorted.push(node).map_err(|_| Error::OverCapacity)?;
-
-            // for each node m with an edge e from n to m, do
-            // remove edge e from the graph
-
-            // keep track of edges that have become starting
-            let mut starting_edges: Vec<bool, CAP> = Vec::new();
-            // fill with default (false)
-            starting_edges.resize_default(CAP).unwrap();
-            for (idx, edge) in edges.iter().enumerate() {
-                if edge.from == node {
-                    // this edge is from a starting node, so mark the edge as starting, too
-                    starting_edges[idx] = true;
-                    // check if m has other incoming edges, if not, add node m to the starting edge set
-                    let mut m_has_become_starting = true;
-                    for check_edge in &edges {
-                        if check_edge.to == edge.to && check_edge.from != edge.from {
-                            m_has_become_starting = false;
-                            break;
+    /// All stored edges, in insertion order.
+    pub fn edges(&self) -> &[Edge<N>] {
+        &self.edges
+    }
+
+    /// The edge at position `idx` of [`Graph::edges`], e.g. as returned by [`Graph::insert_edge_at`].
+    pub fn get_edge(&self, idx: usize) -> Option<Edge<N>> {
+        self.edges.get(idx).copied()
+    }
+
+    /// Number of stored edges, duplicates included.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Number of stored edges, same as [`Graph::edge_count`].
+    pub fn len(&self) -> usize {
+        self.edge_count()
+    }
+
+    /// `true` if the graph has no edges.
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Maximum number of edges the graph can hold, i.e. `EDGES`.
+    pub fn capacity(&self) -> usize {
+        EDGES
+    }
+
+    /// Number of edges that can still be inserted before `Error::OverCapacity`.
+    pub fn remaining_capacity(&self) -> usize {
+        EDGES - self.edges.len()
+    }
+
+    /// Number of edges pointing to `node`.
+    pub fn in_degree(&self, node: N) -> usize {
+        self.edges.iter().filter(|edge| edge.to == node).count()
+    }
+
+    /// Number of edges starting at `node`.
+    pub fn out_degree(&self, node: N) -> usize {
+        self.edges.iter().filter(|edge| edge.from == node).count()
+    }
+
+    /// Number of edges starting or ending at `node`, i.e. `in_degree + out_degree`.
+    ///
+    /// A self-loop counts twice, once in each direction. Parallel edges are counted individually.
+    pub fn degree(&self, node: N) -> usize {
+        self.edges
+            .iter()
+            .map(|edge| usize::from(edge.from == node) + usize::from(edge.to == node))
+            .sum()
+    }
+
+    /// Whether `node` is part of the graph and has no incoming edges, i.e. the per-node version of [`Graph::roots`].
+    ///
+    /// Like `roots`, this includes isolated nodes added via [`Graph::add_node`], which are thus both sources and
+    /// sinks. Nodes that aren't part of the graph are neither. Doesn't allocate, and takes `O(edges)` time.
+    pub fn is_source(&self, node: N) -> bool {
+        self.in_degree(node) == 0 && (self.out_degree(node) > 0 || self.nodes.contains(&node))
+    }
+
+    /// Whether `node` is part of the graph and has no outgoing edges, i.e. the per-node version of
+    /// [`Graph::leaves`]. See [`Graph::is_source`] for isolated and unknown nodes.
+    pub fn is_sink(&self, node: N) -> bool {
+        self.out_degree(node) == 0 && (self.in_degree(node) > 0 || self.nodes.contains(&node))
+    }
+
+    /// Nodes added via [`Graph::add_node`] that no edge references, in the order they were added.
+    fn isolated_nodes(&self) -> impl Iterator<Item = N> + '_ {
+        self.nodes.iter().copied().filter(|&node| {
+            !self
+                .edges
+                .iter()
+                .any(|edge| edge.from == node || edge.to == node)
+        })
+    }
+
+    /// Iterate over the targets of all edges starting at `node`, in edge order.
+    /// Parallel edges are not deduplicated, so a successor is yielded once per edge.
+    pub fn successors(&self, node: N) -> impl Iterator<Item = N> + '_ {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.from == node)
+            .map(|edge| edge.to)
+    }
+
+    /// Iterate over the sources of all edges pointing to `node`, in edge order.
+    /// Parallel edges are not deduplicated, so a predecessor is yielded once per edge.
+    pub fn predecessors(&self, node: N) -> impl Iterator<Item = N> + '_ {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.to == node)
+            .map(|edge| edge.from)
+    }
+
+    /// Iterate over all distinct nodes: those referenced by any edge, followed by
+    /// isolated nodes added via [`Graph::add_node`].
+    ///
+    /// Each node is yielded once, in the order it first appears in the edge list (`from` before `to`).
+    /// This doesn't allocate, at the cost of `O(EDGES²)` time.
+    pub fn nodes(&self) -> impl Iterator<Item = N> + '_ {
+        let edge_nodes = self.edges.iter().enumerate().flat_map(|(idx, edge)| {
+            let seen = |node: N| {
+                self.edges[..idx]
+                    .iter()
+                    .any(|e| e.from == node || e.to == node)
+            };
+            let from = (!seen(edge.from)).then_some(edge.from);
+            let to = (!seen(edge.to) && edge.to != edge.from).then_some(edge.to);
+            from.into_iter().chain(to)
+        });
+        edge_nodes.chain(self.isolated_nodes())
+    }
+
+    /// Iterate over the root nodes, i.e. nodes without incoming edges.
+    /// These are the nodes a topological sort can start with, including isolated nodes added via
+    /// [`Graph::add_node`].
+    ///
+    /// Each root is yielded once: first in the order it appears as `from` in the edge list, followed by the
+    /// isolated nodes, like in [`Graph::nodes`]. This doesn't allocate, at the cost of `O(EDGES²)` time.
+    pub fn roots(&self) -> impl Iterator<Item = N> + '_ {
+        let edge_roots = self.edges.iter().enumerate().filter_map(|(idx, edge)| {
+            let seen = self.edges[..idx].iter().any(|e| e.from == edge.from);
+            (!seen && self.in_degree(edge.from) == 0).then_some(edge.from)
+        });
+        edge_roots.chain(self.isolated_nodes())
+    }
+
+    /// Iterate over the leaf nodes, i.e. nodes without outgoing edges.
+    /// These are the terminal nodes of a dependency graph, including isolated nodes added via [`Graph::add_node`].
+    ///
+    /// Each leaf is yielded once: first in the order it appears as `to` in the edge list, followed by the isolated
+    /// nodes. Like [`Graph::roots`] this doesn't allocate, at the cost of `O(EDGES²)` time.
+    pub fn leaves(&self) -> impl Iterator<Item = N> + '_ {
+        let edge_leaves = self.edges.iter().enumerate().filter_map(|(idx, edge)| {
+            let seen = self.edges[..idx].iter().any(|e| e.to == edge.to);
+            (!seen && self.out_degree(edge.to) == 0).then_some(edge.to)
+        });
+        edge_leaves.chain(self.isolated_nodes())
+    }
+
+    /// Whether there is a directed path of at least one edge from `from` to `to`.
+    ///
+    /// In particular, a node only reaches itself if it lies on a cycle (or has a self-loop). The search works
+    /// on edge indices, so its scratch space is bounded by `EDGES` and it can't fail; in exchange it takes
+    /// `O(edges²)` time. Build the [`Graph::transitive_closure`] when answering many queries.
+    pub fn reachable(&self, from: N, to: N) -> bool {
+        // edges whose `to` node has been / will be expanded
+        let mut seen: Vec<bool, EDGES> = Vec::new();
+        // can't fail: one flag per edge
+        let _ = seen.resize(self.edges.len(), false);
+        // FIFO queue of edge indices, everything past `head` is yet to be expanded
+        let mut queue: Vec<usize, EDGES> = Vec::new();
+        let mut head = 0;
+        let mut expand = from;
+        loop {
+            for (idx, edge) in self.edges.iter().enumerate() {
+                if edge.from == expand && !seen[idx] {
+                    if edge.to == to {
+                        return true;
+                    }
+                    seen[idx] = true;
+                    // can't fail: every edge is pushed at most once
+                    let _ = queue.push(idx);
+                }
+            }
+            let Some(&idx) = queue.get(head) else {
+                return false;
+            };
+            head += 1;
+            expand = self.edges[idx].to;
+        }
+    }
+
+    /// Whether the graph is acyclic, i.e. whether sorting it would succeed (capacity permitting).
+    ///
+    /// Doesn't consume self and never fails: the depth-first search tracks edges rather than nodes, so its scratch
+    /// space is bounded by `EDGES`. In exchange it takes `O(edges²)` time.
+    pub fn is_dag(&self) -> bool {
+        self.find_cycle().is_none()
+    }
+
+    /// Find one cycle, e.g. to show it to the user, or `None` if the graph is acyclic.
+    ///
+    /// The cycle is listed like in `Error::Cycle`: each node has an edge to the next one, and the last node has an
+    /// edge back to the first, which is not repeated. Uses the same depth-first search as [`Graph::is_dag`], so it
+    /// doesn't consume self, never fails, and takes `O(edges²)` time. Cheaper than [`Graph::scc`] if one cycle is
+    /// all you need.
+    ///
+    /// ```
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 4>::from_array([(0, 5), (5, 1), (1, 5)]);
+    /// let cycle = graph.find_cycle().unwrap();
+    /// assert_eq!(&[5, 1], cycle.as_slice());
+    /// // print "5 → 1 → 5"
+    /// let mut text = cycle.iter().map(|node| node.to_string()).collect::<Vec<_>>();
+    /// text.push(cycle[0].to_string());
+    /// assert_eq!("5 → 1 → 5", text.join(" → "));
+    /// ```
+    pub fn find_cycle(&self) -> Option<Vec<N, EDGES>> {
+        // edges whose target has been explored completely
+        let mut done: Vec<bool, EDGES> = Vec::new();
+        // can't fail: one flag per edge
+        let _ = done.resize(self.edges.len(), false);
+        // the current DFS path as edge indices, each with the position to continue scanning for outgoing edges of
+        // its target at
+        let mut path: Vec<(usize, usize), EDGES> = Vec::new();
+        for root in 0..self.edges.len() {
+            if done[root] {
+                continue;
+            }
+            // can't fail: the path holds every edge at most once
+            let _ = path.push((root, 0));
+            while let Some((current, next)) = path.last_mut() {
+                let node = self.edges[*current].to;
+                let edges = &self.edges;
+                let found = (*next..edges.len()).find(|&idx| edges[idx].from == node && !done[idx]);
+                match found {
+                    Some(idx) => {
+                        *next = idx + 1;
+                        let to = edges[idx].to;
+                        // leading back onto the current path closes a cycle
+                        if to == node {
+                            return Some([node].into_iter().collect());
                         }
+                        if let Some(start) =
+                            path.iter().position(|&(edge, _)| edges[edge].from == to)
+                        {
+                            // can't fail: one node per edge of the cycle
+                            let mut cycle: Vec<N, EDGES> = path[start..]
+                                .iter()
+                                .map(|&(edge, _)| edges[edge].from)
+                                .collect();
+                            let _ = cycle.push(node);
+                            return Some(cycle);
+                        }
+                        let _ = path.push((idx, 0));
                     }
-                    if m_has_become_starting {
-                        starting_nodes
-                            .insert(edge.to)
-                            .map_err(|_| Error::OverCapacity)?;
+                    None => {
+                        done[*current] = true;
+                        path.pop();
                     }
                 }
             }
+        }
+        None
+    }
 
-            // retain all edges that have *not* been flagged as starting
-            // unwrap safety: number of starting edges <= total number of edges,
-            // hence the iterator never gets exhausted
-            let mut it = starting_edges.into_iter();
-            edges.retain(|_| !it.next().unwrap());
+    /// Collect the nodes that have an edge to themselves into `out`, each once, in edge order.
+    ///
+    /// A self-loop is the simplest possible cycle, and makes every sort fail with `Error::Cycle`. `out` is cleared
+    /// on entry. Returns `Error::OverCapacity` if it is too small, in which case it contains a prefix of the result.
+    pub fn self_loops<const LEN: usize>(
+        &self,
+        out: &mut Vec<N, LEN>,
+    ) -> Result<(), Error<N, EDGES>> {
+        out.clear();
+        let is_loop = |edge: &Edge<N>| edge.from == edge.to;
+        let first_loops = self.edges.iter().enumerate().filter(|&(idx, edge)| {
+            is_loop(edge)
+                && !self.edges[..idx]
+                    .iter()
+                    .any(|e| is_loop(e) && e.from == edge.from)
+        });
+        for (_, edge) in first_loops.clone() {
+            out.push(edge.from).map_err(|_| Error::OverCapacity {
+                needed: first_loops.clone().count(),
+            })?;
         }
-        if edges.is_empty() {
-            Ok(topo_sorted)
-        } else {
-            Err(Error::Cycle)
+        Ok(())
+    }
+
+    /// Whether any edge is present more than once. Duplicates don't affect sorting, but each one takes up capacity,
+    /// see [`Graph::insert_edge_unique`]. Takes `O(edges²)` time.
+    pub fn has_parallel_edges(&self) -> bool {
+        self.edges
+            .iter()
+            .enumerate()
+            .any(|(idx, edge)| self.edges[..idx].contains(edge))
+    }
+
+    /// Collect every edge that is present more than once into `out`, each once, in order of first occurrence.
+    ///
+    /// `out` is cleared on entry. Returns `Error::OverCapacity` if it is too small, in which case it contains a
+    /// prefix of the result.
+    pub fn parallel_edges<const LEN: usize>(
+        &self,
+        out: &mut Vec<Edge<N>, LEN>,
+    ) -> Result<(), Error<N, EDGES>> {
+        out.clear();
+        let first_parallels = self.edges.iter().enumerate().filter(|&(idx, edge)| {
+            !self.edges[..idx].contains(edge) && self.edges[idx + 1..].contains(edge)
+        });
+        for (_, edge) in first_parallels.clone() {
+            out.push(*edge).map_err(|_| Error::OverCapacity {
+                needed: first_parallels.clone().count(),
+            })?;
         }
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    const CAPACITY: usize = 32;
-    #[test]
-    fn ok() {
-        // the first 4 edges imply the only possible topological sorting is 1,2,3,4,5
-        let edge_data = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)];
-        let mut edges: heapless::Vec<Edge, CAPACITY> = heapless::Vec::new();
+    /// Whether inserting `edge` would close a cycle, i.e. whether `edge.to` already reaches `edge.from`
+    /// (or the edge is a self-loop). Same cost as [`Graph::reachable`].
+    pub fn would_create_cycle(&self, edge: Edge<N>) -> bool {
+        edge.from == edge.to || self.reachable(edge.to, edge.from)
+    }
 
-        // construct graph from a reversed iter to reduce likelihood of accidental success
-        for edge in edge_data.into_iter().rev() {
-            edges
-                .push(edge.into())
-                .expect("bug in test case: edge vec over capacity");
+    /// Collect all nodes reachable from `node` via at least one edge into `out`, in breadth-first order.
+    ///
+    /// `node` itself is only included if it lies on a cycle, see [`Graph::reachable`]. `out` is cleared on entry
+    /// and doubles as the search queue, so no other scratch space is needed. Returns `Error::OverCapacity` if
+    /// it is too small, in which case it contains a prefix of the result.
+    pub fn descendants<const LEN: usize>(
+        &self,
+        node: N,
+        out: &mut Vec<N, LEN>,
+    ) -> Result<(), Error<N, EDGES>> {
+        self.search_into(node, out, |edge| (edge.from, edge.to))
+    }
+
+    /// Collect all nodes from which `node` is reachable via at least one edge into `out`, in breadth-first order.
+    ///
+    /// The counterpart to [`Graph::descendants`], following edges backwards.
+    pub fn ancestors<const LEN: usize>(
+        &self,
+        node: N,
+        out: &mut Vec<N, LEN>,
+    ) -> Result<(), Error<N, EDGES>> {
+        self.search_into(node, out, |edge| (edge.to, edge.from))
+    }
+
+    /// Breadth-first search from `start` along edges as oriented by `direction`, with `out` as the queue.
+    fn search_into<const LEN: usize>(
+        &self,
+        start: N,
+        out: &mut Vec<N, LEN>,
+        direction: impl Fn(&Edge<N>) -> (N, N),
+    ) -> Result<(), Error<N, EDGES>> {
+        out.clear();
+        // everything in `out` past `head` is yet to be expanded
+        let mut head = 0;
+        let mut expand = start;
+        loop {
+            for edge in &self.edges {
+                let (from, to) = direction(edge);
+                if from == expand && !out.contains(&to) {
+                    // the full result isn't known without more memory
+                    out.push(to)
+                        .map_err(|_| Error::OverCapacity { needed: LEN + 1 })?;
+                }
+            }
+            let Some(&next) = out.get(head) else {
+                return Ok(());
+            };
+            head += 1;
+            expand = next;
         }
-        let graph = Graph::new_with_edges(edges);
-        let res = graph.into_topo_sorted();
-        let expected = [1, 2, 3, 4, 5].as_slice().try_into().unwrap();
-        assert_eq!(Ok(expected), res);
     }
 
-    #[test]
-    fn ok_with_push() {
-        // the first 4 edges imply the only possible topological sorting is 1,2,3,4,5
-        let edge_data = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)];
-        let mut graph = Graph::<CAPACITY>::new();
-        for edge in edge_data.into_iter() {
-            graph.insert_edge(edge.into()).unwrap();
+    /// The largest node id referenced by an edge or added via [`Graph::add_node`], or `None` for an empty graph.
+    /// Handy for sizing external per-node arrays as `max_node_id + 1`. Allocation-free.
+    pub fn max_node_id(&self) -> Option<N>
+    where
+        N: Ord,
+    {
+        self.edges
+            .iter()
+            .flat_map(|edge| [edge.from, edge.to])
+            .chain(self.nodes.iter().copied())
+            .max()
+    }
+
+    /// The smallest node id, see [`Graph::max_node_id`].
+    pub fn min_node_id(&self) -> Option<N>
+    where
+        N: Ord,
+    {
+        self.edges
+            .iter()
+            .flat_map(|edge| [edge.from, edge.to])
+            .chain(self.nodes.iter().copied())
+            .min()
+    }
+
+    /// Count the distinct nodes, i.e. nodes referenced by any edge plus nodes added via [`Graph::add_node`].
+    /// Returns `Error::OverCapacity` if there are more than `NODES` of them.
+    pub fn node_count(&self) -> Result<usize, Error<N, EDGES>> {
+        Ok(self.node_set()?.len())
+    }
+
+    /// The smallest capacity that lets [`Graph::into_topo_sorted`] succeed on this graph (as far as capacity is
+    /// concerned: a cycle still is an error), to pick `EDGES` and `NODES` offline.
+    ///
+    /// The formula is `max(edges, nodes)`, where `edges` is [`Graph::edge_count`] and `nodes` is the number of
+    /// distinct nodes, counting both edge endpoints and nodes added via [`Graph::add_node`]. `EDGES` needs to hold
+    /// the edges, and `NODES` needs to hold the nodes, which also bounds the set of starting nodes and the sorted
+    /// output; the maximum works for the `Graph<N, CAPACITY>` shorthand, where `NODES` defaults to `EDGES`. Unlike
+    /// [`Graph::node_count`] this doesn't fail if the nodes exceed `NODES`; they are then counted in `O(n²)` time.
+    ///
+    /// ```
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 8>::from_array([(0, 1), (1, 2)]);
+    /// assert_eq!(3, graph.required_capacity());
+    /// ```
+    pub fn required_capacity(&self) -> usize {
+        let nodes = match self.node_set() {
+            Ok(set) => set.len(),
+            Err(_) => count_distinct_nodes(&self.nodes, &self.edges),
+        };
+        nodes.max(self.edges.len())
+    }
+
+    /// Collect the distinct nodes.
+    fn node_set(&self) -> Result<NodeIndex<N, NODES>, Error<N, EDGES>> {
+        let mut nodes = NodeIndex::new();
+        let endpoints = self.edges.iter().flat_map(|edge| [edge.from, edge.to]);
+        for node in self.nodes.iter().copied().chain(endpoints) {
+            nodes
+                .insert(node)
+                .ok_or_else(|| Error::nodes_over_capacity(&self.nodes, &self.edges))?;
         }
-        let res = graph.into_topo_sorted();
-        let expected = [1, 2, 3, 4, 5].as_slice().try_into().unwrap();
-        assert_eq!(Ok(expected), res);
+        Ok(nodes)
     }
 
-    #[test]
-    fn ok_branching() {
-        // the first 4 edges imply the only possible topological sorting is 1,2,3,4,5
-        let edge_data = [(1, 2), (1, 3), (2, 4), (3, 4)];
-        let mut graph = Graph::<CAPACITY>::new();
-        for edge in edge_data.into_iter() {
-            graph.insert_edge(edge.into()).unwrap();
+    /// Extract the part of the graph induced by `nodes`.
+    ///
+    /// Keeps exactly the edges with both endpoints in `nodes`; edges with one endpoint outside are dropped.
+    /// Nodes of the set that are part of the graph but lose all their edges are kept as added nodes, so sorting the
+    /// subgraph yields every selected node. Returns `Error::OverCapacity` if there are more of those than `NODES`.
+    pub fn subgraph(&self, nodes: &[N]) -> Result<Self, Error<N, EDGES>> {
+        self.subgraph_by(|node| nodes.contains(&node))
+    }
+
+    /// Like [`Graph::subgraph`], but selects the nodes to keep with a predicate.
+    pub fn subgraph_by(&self, keep: impl Fn(N) -> bool) -> Result<Self, Error<N, EDGES>> {
+        let mut subgraph = Self::new();
+        for &edge in &self.edges {
+            if keep(edge.from) && keep(edge.to) {
+                // can't fail: there are at most as many edges as in self
+                subgraph.insert_edge(edge)?;
+            }
         }
-        let res = graph.into_topo_sorted();
-        // there is no unique toposort of this graph, so we can just assert ok
-        // if we don't want to rely on implementation details
-        assert!(res.is_ok());
+        for node in self.nodes() {
+            let connected = subgraph
+                .edges
+                .iter()
+                .any(|edge| edge.from == node || edge.to == node);
+            if keep(node) && !connected {
+                subgraph.add_node(node)?;
+            }
+        }
+        Ok(subgraph)
     }
 
-    #[test]
-    fn err_too_many_edges() {
-        let mut graph = Graph::<1>::new();
-        assert_eq!(Ok(()), graph.insert_edge((1, 2).into()));
-        assert_eq!(Err(Error::OverCapacity), graph.insert_edge((2, 3).into()));
+    /// Remove all edges and added nodes, keeping the storage for reuse.
+    pub fn clear(&mut self) {
+        self.edges.clear();
+        self.nodes.clear();
     }
 
-    #[test]
-    fn err_num_nodes_greater_than_num_edges() {
-        let mut graph = Graph::<2>::new();
-        assert_eq!(Ok(()), graph.insert_edge((1, 2).into()));
-        assert_eq!(Ok(()), graph.insert_edge((0, 1).into()));
-        assert_eq!(Err(Error::OverCapacity), graph.into_topo_sorted());
+    /// Flip the direction of every edge in place.
+    /// Sorting the reversed graph yields a reverse dependency order, e.g. for teardown sequencing.
+    pub fn reverse(mut self) -> Self {
+        for edge in self.edges.iter_mut() {
+            *edge = edge.reversed();
+        }
+        self
     }
 
-    #[test]
-    fn err_cycles() {
-        let edge_data = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5), (5, 1)];
-        let mut edges: heapless::Vec<Edge, CAPACITY> = heapless::Vec::new();
+    /// compute topological sort, consuming self.
+    /// Every node referenced by an edge or added via [`Graph::add_node`] occurs exactly once in the output.
+    /// On failure due to a cycle, the returned `Error::Cycle` contains the nodes of (at least) one cycle.
+    /// See the crate docs on how ties between valid orders are broken.
+    /// The temporary data structures live on the stack for the duration of the call; to keep them around between
+    /// sorts instead, use [`Graph::into_topo_sorted_with`].
+    pub fn into_topo_sorted(self) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        self.topo_sorted()
+    }
 
-        for edge in edge_data {
-            edges
-                .push(edge.into())
-                .expect("bug in test case: edge vec over capacity");
+    /// compute reverse topological sort (sinks first, sources last), consuming self.
+    ///
+    /// This is exactly the output of [`Graph::into_topo_sorted`] reversed, so both orders are equally
+    /// deterministic. Useful for teardown, where dependents must go before their dependencies.
+    pub fn into_topo_sorted_rev(self) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        let mut sorted = self.topo_sorted()?;
+        sorted.reverse();
+        Ok(sorted)
+    }
+
+    /// compute topological sort, leaving self intact.
+    /// Same as [`Graph::into_topo_sorted`], for when you want to keep editing and re-sorting the graph.
+    pub fn topo_sorted(&self) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        let mut topo_sorted = Vec::new();
+        self.topo_sort_into(&mut topo_sorted)?;
+        Ok(topo_sorted)
+    }
+
+    /// compute topological sort into a caller-provided buffer, leaving self intact.
+    ///
+    /// `out` is cleared on entry. Returns `Error::OverCapacity` if it is too small to hold all nodes,
+    /// in which case it contains a prefix of the order. On `Error::Cycle`, `out` is left empty.
+    pub fn topo_sort_into<const LEN: usize>(
+        &self,
+        out: &mut Vec<N, LEN>,
+    ) -> Result<(), Error<N, EDGES>> {
+        out.clear();
+        let (order, adjacency) = self.kahn()?;
+        self.check_complete(&order, &adjacency)?;
+        let needed = order.len();
+        for idx in order {
+            out.push(adjacency.node(idx))
+                .map_err(|_| Error::OverCapacity { needed })?;
         }
-        let graph = Graph::new_with_edges(edges);
-        let res = graph.into_topo_sorted();
-        assert_eq!(Err(Error::Cycle), res);
+        Ok(())
+    }
+
+    /// compute the lexicographically smallest topological sort, consuming self.
+    ///
+    /// Whenever several nodes are ready, the smallest node id comes first. The result only depends on the
+    /// graph's structure, not on edge insertion order.
+    pub fn into_topo_sorted_lex(self) -> Result<Vec<N, NODES>, Error<N, EDGES>>
+    where
+        N: Ord,
+    {
+        let mut adjacency = Adjacency::new(&self.nodes, &self.edges)?;
+        let order = adjacency.kahn_min_by_key(|node, _| node);
+        self.collect_order(&order, &adjacency)
+    }
+
+    /// compute a stable topological sort, consuming self.
+    ///
+    /// Whenever several nodes are ready, the one that was seen first comes first, so the output follows
+    /// registration order as closely as the edges allow. Nodes added via [`Graph::add_node`] count as seen
+    /// first, followed by edge endpoints in edge order.
+    pub fn into_topo_sorted_stable(self) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        let mut adjacency = Adjacency::new(&self.nodes, &self.edges)?;
+        // dense indices are assigned in order of first appearance
+        let order = adjacency.kahn_min_by_key(|_, idx| idx);
+        self.collect_order(&order, &adjacency)
+    }
+
+    /// compute topological sort with custom tie-breaking, consuming self.
+    ///
+    /// Whenever several nodes are ready, the smallest one according to `cmp` comes first, e.g. to prioritize
+    /// nodes by externally known weights. `cmp` is only ever called to compare nodes that are ready at the same
+    /// time; if it considers them equal, the one that became ready first wins.
+    ///
+    /// Every step scans all ready nodes, so this takes `O(NODES²)` comparisons in the worst case
+    /// (a graph where most nodes are ready at once), compared to the linear time of [`Graph::into_topo_sorted`].
+    pub fn into_topo_sorted_by<F: FnMut(N, N) -> Ordering>(
+        self,
+        cmp: F,
+    ) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        let mut adjacency = Adjacency::new(&self.nodes, &self.edges)?;
+        let order = adjacency.kahn_min_by(cmp);
+        self.collect_order(&order, &adjacency)
+    }
+
+    /// compute topological sort using depth-first search instead of Kahn's algorithm, consuming self.
+    ///
+    /// The result is the reverse DFS post-order, which tends to keep chains of related nodes together.
+    /// The search uses an explicit stack rather than recursion, so deep graphs can't overflow the call stack.
+    pub fn into_topo_sorted_dfs(self) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        let adjacency: Adjacency<N, EDGES, NODES> = Adjacency::new(&self.nodes, &self.edges)?;
+        let order = adjacency.dfs_post_order()?;
+        Ok(order.iter().rev().map(|&idx| adjacency.node(idx)).collect())
+    }
+
+    /// group the nodes into levels of mutually independent nodes, consuming self.
+    ///
+    /// Level 0 holds all roots, level 1 everything whose predecessors are all in level 0, and so on. No two nodes
+    /// of the same level are connected by an edge, so each level can be processed in parallel once the previous
+    /// ones are done. Concatenating the levels gives exactly the order of [`Graph::into_topo_sorted`].
+    ///
+    /// Note that the result reserves `NODES` levels of `NODES` nodes each, i.e. it takes
+    /// `O(NODES²)` memory, which may be too much for the stack of small targets.
+    pub fn into_levels(self) -> Result<Levels<N, NODES>, Error<N, EDGES>> {
+        let mut adjacency = Adjacency::new(&self.nodes, &self.edges)?;
+        let (order, ends) = adjacency.kahn_levels();
+        self.check_complete(&order, &adjacency)?;
+        let mut levels = Vec::new();
+        let mut start = 0;
+        for end in ends {
+            let level = order[start..end]
+                .iter()
+                .map(|&idx| adjacency.node(idx))
+                .collect();
+            // can't fail: there are at most as many levels as nodes
+            let _ = levels.push(level);
+            start = end;
+        }
+        Ok(levels)
+    }
+
+    /// record the ready frontier before every step of the sort, consuming self. Mainly a teaching and diagnostic tool.
+    ///
+    /// Kahn's algorithm removes one ready node (one without remaining incoming edges) per step. Entry `i` of the
+    /// result holds the nodes that were ready right before step `i`, in the order they became ready; its first node
+    /// is the one removed, i.e. the first nodes of all frontiers make up [`Graph::into_topo_sorted`]. Unlike
+    /// [`Graph::into_levels`], where a level only starts once the previous one is done, a node enters the frontier
+    /// as soon as its last predecessor is removed, so it can share a frontier with nodes of the previous level.
+    ///
+    /// ```
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 4>::from_array([(0, 2), (1, 3)]);
+    /// let frontiers = graph.into_frontiers().ok().unwrap();
+    /// // 2 became ready when 0 was removed, before 1 was
+    /// assert_eq!(&[1, 2], frontiers[1].as_slice());
+    /// assert_eq!(4, frontiers.len());
+    /// ```
+    ///
+    /// Like `into_levels`, the result takes `O(NODES²)` memory.
+    pub fn into_frontiers(self) -> Result<Frontiers<N, NODES>, Error<N, EDGES>> {
+        let mut adjacency = Adjacency::new(&self.nodes, &self.edges)?;
+        // FIFO queue of ready nodes: everything past `head` is the current frontier
+        let mut queue = adjacency.starting_nodes();
+        let mut frontiers = Vec::new();
+        for head in 0.. {
+            let Some(&idx) = queue.get(head) else {
+                break;
+            };
+            let frontier = queue[head..]
+                .iter()
+                .map(|&idx| adjacency.node(idx))
+                .collect();
+            // can't fail: there is one frontier per node
+            let _ = frontiers.push(frontier);
+            // can't fail: every node is pushed at most once
+            adjacency.remove_outgoing(idx, |succ| {
+                let _ = queue.push(succ);
+            });
+        }
+        self.check_complete(&queue, &adjacency)?;
+        Ok(frontiers)
+    }
+
+    /// size of the largest level of [`Graph::into_levels`], consuming self.
+    ///
+    /// This is the maximum number of nodes that can be processed in parallel, e.g. to size a worker pool.
+    /// Unlike `into_levels` it only needs `O(NODES)` memory. The empty graph has width 0.
+    pub fn max_width(self) -> Result<usize, Error<N, EDGES>> {
+        let mut adjacency = Adjacency::new(&self.nodes, &self.edges)?;
+        let (order, ends) = adjacency.kahn_levels();
+        self.check_complete(&order, &adjacency)?;
+        let mut start = 0;
+        let mut width = 0;
+        for end in ends {
+            width = width.max(end - start);
+            start = end;
+        }
+        Ok(width)
+    }
+
+    /// call `f` with every elementary cycle, consuming self, e.g. to show all tangles of a config at once.
+    ///
+    /// An elementary cycle visits no node twice. Each one is listed like in `Error::Cycle` (each node has an edge to
+    /// the next one, and the last node has an edge back to the first), starting at the node that appears first in
+    /// the graph; parallel edges don't produce duplicates. Uses Johnson's algorithm, producing cycles one at a time
+    /// into a single buffer, so it takes `O(NODES + EDGES)` memory. The number of cycles can grow exponentially with
+    /// the size of the graph though, so prefer [`Graph::find_cycle`] if one is enough.
+    ///
+    /// ```rust
+    /// # use heapless_topo::Graph;
+    /// // two cycles sharing the node 1
+    /// let graph = Graph::<u8, 8>::from_array([(0, 1), (1, 2), (2, 0), (1, 3), (3, 1)]);
+    /// let mut count = 0;
+    /// graph
+    ///     .all_cycles(|cycle| {
+    ///         assert!(cycle == [0, 1, 2] || cycle == [1, 3]);
+    ///         count += 1;
+    ///     })
+    ///     .ok()
+    ///     .unwrap();
+    /// assert_eq!(2, count);
+    /// ```
+    pub fn all_cycles(self, mut f: impl FnMut(&[N])) -> Result<(), Error<N, EDGES>> {
+        let adjacency: Adjacency<N, EDGES, NODES> = Adjacency::new(&self.nodes, &self.edges)?;
+        let mut nodes: Vec<N, NODES> = Vec::new();
+        adjacency.for_each_cycle(|cycle| {
+            nodes.clear();
+            // can't fail: `cycle` has at most NODES entries
+            nodes.extend(cycle.iter().map(|&idx| adjacency.node(idx)));
+            f(&nodes);
+        });
+        Ok(())
+    }
+
+    /// call `f` with every valid topological order, consuming self.
+    ///
+    /// Useful for testing code that must work for any valid order. Orders are produced one at a time into
+    /// a single buffer, so this takes `O(NODES)` memory. However, the *number* of orders grows combinatorially:
+    /// `n` independent nodes alone have `n!` of them, so only use this on small or tightly constrained graphs.
+    /// Returns `Error::Cycle` (without calling `f`) for cyclic graphs.
+    ///
+    /// ```rust
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 4>::from_array([(0, 1), (0, 2)]);
+    /// let mut count = 0;
+    /// graph
+    ///     .all_topo_sorts(|order| {
+    ///         assert_eq!(0, order[0]);
+    ///         count += 1;
+    ///     })
+    ///     .ok()
+    ///     .unwrap();
+    /// assert_eq!(2, count);
+    /// ```
+    pub fn all_topo_sorts(self, mut f: impl FnMut(&[N])) -> Result<(), Error<N, EDGES>> {
+        let (order, adjacency) = self.kahn()?;
+        self.check_complete(&order, &adjacency)?;
+        // `kahn` consumed the in-degrees, start over
+        let mut adjacency: Adjacency<N, EDGES, NODES> = Adjacency::new(&self.nodes, &self.edges)?;
+        let mut nodes: Vec<N, NODES> = Vec::new();
+        adjacency.for_each_order(|order, adjacency| {
+            nodes.clear();
+            // can't fail: `order` has at most NODES entries
+            nodes.extend(order.iter().map(|&idx| adjacency.node(idx)));
+            f(&nodes);
+        });
+        Ok(())
+    }
+
+    /// count the valid topological orders, consuming self.
+    ///
+    /// A count of exactly 1 means the edges fully determine the order (see [`Graph::has_unique_order`] for a much
+    /// cheaper check of just that). The orders are counted by backtracking over all of them like
+    /// [`Graph::all_topo_sorts`], so the running time is proportional to the result, which can grow
+    /// combinatorially. The count saturates at `u64::MAX` rather than overflowing, though a graph with that many
+    /// orders won't finish counting anyway. Returns `Error::Cycle` for cyclic graphs.
+    pub fn count_topo_sorts(self) -> Result<u64, Error<N, EDGES>> {
+        let mut count: u64 = 0;
+        self.all_topo_sorts(|_| count = count.saturating_add(1))?;
+        Ok(count)
+    }
+
+    /// whether there is exactly one valid topological order, consuming self.
+    ///
+    /// That's the case iff Kahn's algorithm never has more than one ready node to choose from, i.e. every level of
+    /// [`Graph::into_levels`] holds a single node. Runs in linear time. Returns `Error::Cycle` for cyclic graphs.
+    pub fn has_unique_order(self) -> Result<bool, Error<N, EDGES>> {
+        let mut adjacency = Adjacency::new(&self.nodes, &self.edges)?;
+        let (order, ends) = adjacency.kahn_levels();
+        self.check_complete(&order, &adjacency)?;
+        Ok(ends.len() == order.len())
+    }
+
+    /// the position of every node in the order of [`Graph::into_topo_sorted`], consuming self.
+    ///
+    /// Saves scanning the sorted output whenever you need to know where a node landed. Returns `Error::Cycle` for
+    /// cyclic graphs.
+    ///
+    /// ```
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 4>::from_array([(7, 3), (5, 7)]);
+    /// let ranks = graph.ranks().ok().unwrap();
+    /// assert_eq!(Some(0), ranks.get(5));
+    /// assert_eq!(Some(2), ranks.get(3));
+    /// assert_eq!(None, ranks.get(4));
+    /// ```
+    pub fn ranks(self) -> Result<NodeMap<N, usize, NODES>, Error<N, EDGES>> {
+        let (order, adjacency) = self.kahn()?;
+        self.check_complete(&order, &adjacency)?;
+        let mut ranks: Vec<usize, NODES> = Vec::new();
+        // can't fail: one entry per node
+        let _ = ranks.resize(order.len(), 0);
+        for (rank, &idx) in order.iter().enumerate() {
+            ranks[idx] = rank;
+        }
+        Ok(NodeMap::new(adjacency.into_nodes(), ranks))
+    }
+
+    /// the depth of every node, i.e. the number of edges on the longest path reaching it from a root, consuming self.
+    ///
+    /// Roots have depth 0. In a task graph with unit costs this is the earliest step a task can start in.
+    /// Returns `Error::Cycle` for cyclic graphs.
+    ///
+    /// ```
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 4>::from_array([(0, 1), (1, 2), (0, 2)]);
+    /// let depths = graph.depths().ok().unwrap();
+    /// assert_eq!(Some(0), depths.get(0));
+    /// assert_eq!(Some(2), depths.get(2));
+    /// ```
+    pub fn depths(self) -> Result<NodeMap<N, usize, NODES>, Error<N, EDGES>> {
+        let (order, adjacency) = self.kahn()?;
+        self.check_complete(&order, &adjacency)?;
+        let (lengths, _) = adjacency.longest_paths(&order);
+        Ok(NodeMap::new(adjacency.into_nodes(), lengths))
+    }
+
+    /// the height of every node, i.e. the number of edges on the longest path from it to a leaf, consuming self.
+    ///
+    /// Leaves have height 0. Together with [`Graph::depths`] this gives the slack of a task with unit costs:
+    /// `critical_path_len - depth - height` is how many steps it can be delayed without delaying the whole graph.
+    /// Returns `Error::Cycle` for cyclic graphs.
+    ///
+    /// ```
+    /// # use heapless_topo::Graph;
+    /// let graph = Graph::<u8, 4>::from_array([(0, 1), (1, 2), (0, 2)]);
+    /// let heights = graph.heights().ok().unwrap();
+    /// assert_eq!(Some(2), heights.get(0));
+    /// assert_eq!(Some(0), heights.get(2));
+    /// ```
+    pub fn heights(self) -> Result<NodeMap<N, usize, NODES>, Error<N, EDGES>> {
+        let (order, adjacency) = self.kahn()?;
+        self.check_complete(&order, &adjacency)?;
+        let heights = adjacency.heights(&order);
+        Ok(NodeMap::new(adjacency.into_nodes(), heights))
+    }
+
+    /// number of edges on the longest path through the graph, consuming self.
+    ///
+    /// In a task graph this is the critical path, which bounds the completion time no matter how much work can
+    /// happen in parallel. Graphs without edges have length 0. Returns `Error::Cycle` for cyclic graphs.
+    pub fn critical_path_len(self) -> Result<usize, Error<N, EDGES>> {
+        let (order, adjacency) = self.kahn()?;
+        self.check_complete(&order, &adjacency)?;
+        let (lengths, _) = adjacency.longest_paths(&order);
+        Ok(lengths.iter().copied().max().unwrap_or(0))
+    }
+
+    /// the nodes of a longest path through the graph, consuming self.
+    ///
+    /// The path has [`Graph::critical_path_len`] edges and thus one more node, unless the graph is empty.
+    /// If there are several longest paths, the one ending first in [`Graph::into_topo_sorted`] order is returned.
+    pub fn critical_path(self) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        let (order, adjacency) = self.kahn()?;
+        self.check_complete(&order, &adjacency)?;
+        let (lengths, predecessors) = adjacency.longest_paths(&order);
+        let mut path = Vec::new();
+        let Some(mut idx) = order.iter().copied().reduce(|longest, idx| {
+            if lengths[idx] > lengths[longest] {
+                idx
+            } else {
+                longest
+            }
+        }) else {
+            return Ok(path);
+        };
+        loop {
+            // can't fail: a path visits every node at most once
+            let _ = path.push(adjacency.node(idx));
+            match predecessors[idx] {
+                usize::MAX => break,
+                pred => idx = pred,
+            }
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// compute the transitive closure, consuming self.
+    ///
+    /// The result has an edge `(a, b)` for every node `b` that is reachable from `a` via one or more edges,
+    /// including `(a, a)` if `a` lies on a cycle. Nodes added via [`Graph::add_node`] are carried over.
+    /// Edges are ordered by source in order of first appearance, and by distance from the source within that.
+    ///
+    /// # Capacity
+    /// The closure of a graph with `n` nodes can have up to `n²` edges (e.g. `n * (n - 1) / 2` for a simple chain),
+    /// far more than the original graph, so its edge capacity `CLOSURE` is a separate const generic. Returns
+    /// `Error::OverCapacity` with the size of the full closure if it is too small.
+    ///
+    /// ```rust
+    /// # use heapless_topo::{Edge, Graph};
+    /// let graph = Graph::<u8, 4>::from_array([(0, 1), (1, 2), (2, 3)]);
+    /// let closure = graph.transitive_closure::<4>();
+    /// assert!(closure == Err(heapless_topo::Error::OverCapacity { needed: 6 }));
+    ///
+    /// let graph = Graph::<u8, 4>::from_array([(0, 1), (1, 2), (2, 3)]);
+    /// let closure = graph.transitive_closure::<8>().ok().unwrap();
+    /// assert_eq!(6, closure.edge_count());
+    /// assert!(closure.contains_edge(Edge::new(0, 3)));
+    /// ```
+    pub fn transitive_closure<const CLOSURE: usize>(
+        self,
+    ) -> Result<Graph<N, CLOSURE, NODES>, Error<N, EDGES>> {
+        let adjacency: Adjacency<N, EDGES, NODES> = Adjacency::new(&self.nodes, &self.edges)?;
+        let mut edges = Vec::new();
+        let mut needed = 0;
+        for from in 0..adjacency.len() {
+            adjacency.for_each_descendant(&[from], |to| {
+                needed += 1;
+                // keep counting once full, to report the size of the whole closure
+                let _ = edges.push(Edge::new(adjacency.node(from), adjacency.node(to)));
+            });
+        }
+        if needed > CLOSURE {
+            return Err(Error::OverCapacity { needed });
+        }
+        Ok(Graph {
+            edges,
+            nodes: self.nodes,
+        })
+    }
+
+    /// compute the transitive reduction, consuming self.
+    ///
+    /// Removes every edge `(a, c)` that is implied by a longer path from `a` to `c`, as well as duplicate edges,
+    /// leaving the smallest graph with the same reachability. The remaining edges keep their order. Only defined
+    /// for acyclic graphs, returns `Error::Cycle` otherwise. Takes `O(nodes * (nodes + edges))` time.
+    ///
+    /// ```rust
+    /// # use heapless_topo::{Edge, Graph};
+    /// let graph = Graph::<u8, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)]);
+    /// let reduced = graph.transitive_reduction().ok().unwrap();
+    /// assert!(reduced.edges() == [Edge::new(1, 2), Edge::new(2, 3), Edge::new(3, 4), Edge::new(4, 5)]);
+    /// ```
+    pub fn transitive_reduction(mut self) -> Result<Self, Error<N, EDGES>> {
+        let (order, adjacency) = self.kahn()?;
+        self.check_complete(&order, &adjacency)?;
+
+        let mut keep: Vec<bool, EDGES> = Vec::new();
+        // can't fail: one flag per edge
+        let _ = keep.resize(self.edges.len(), false);
+        let mut implied: Vec<bool, NODES> = Vec::new();
+        let _ = implied.resize(adjacency.len(), false);
+        for from in 0..adjacency.len() {
+            // everything reachable via a successor of `from` is implied
+            implied.iter_mut().for_each(|flag| *flag = false);
+            adjacency.for_each_descendant(adjacency.successors(from), |idx| implied[idx] = true);
+            let from = adjacency.node(from);
+            for (pos, edge) in self.edges.iter().enumerate() {
+                if edge.from != from {
+                    continue;
+                }
+                // unwrap safety: all endpoints are indexed
+                let to = adjacency.index(edge.to).unwrap();
+                // also marking kept targets as implied drops later duplicates
+                keep[pos] = !implied[to];
+                implied[to] = true;
+            }
+        }
+        let mut keep = keep.iter();
+        self.edges.retain(|_| keep.next().copied().unwrap_or(false));
+        Ok(self)
+    }
+
+    /// compute topological sort as far as possible, consuming self.
+    ///
+    /// Returns the sorted prefix together with the edges that could not be scheduled. For an acyclic graph
+    /// the residual edges are empty and the prefix is the full topological sort. Otherwise the prefix is a valid
+    /// topological order of the acyclic portion, and the residual edges contain every cycle plus everything
+    /// downstream of one.
+    ///
+    /// Returns `Error::OverCapacity` if the temporary data structures overflow; `Error::Cycle` is never returned.
+    pub fn into_topo_sorted_partial(self) -> Result<PartialSort<N, EDGES, NODES>, Error<N, EDGES>> {
+        let (order, adjacency) = self.kahn()?;
+        let topo_sorted = order.iter().map(|&idx| adjacency.node(idx)).collect();
+        let mut edges = self.edges;
+        // an edge is consumed once its `from` node is sorted, everything else is residual
+        edges.retain(|edge| !adjacency.is_sorted(edge.from));
+        Ok((topo_sorted, edges))
+    }
+
+    /// Run Kahn's algorithm, returning the (possibly partial) order as dense indices, and the adjacency list
+    /// for translating them and for inspecting what's left over.
+    fn kahn(&self) -> Result<(Vec<usize, NODES>, Adjacency<N, EDGES, NODES>), Error<N, EDGES>> {
+        let mut adjacency = Adjacency::new(&self.nodes, &self.edges)?;
+
+        // Kahn's algorithm
+        // https://en.wikipedia.org/wiki/Topological_sorting#Kahn's_algorithm
+        let order = adjacency.kahn();
+        Ok((order, adjacency))
+    }
+
+    /// Translate a complete order of dense indices to node ids, or report the cycle that stopped it.
+    fn collect_order(
+        &self,
+        order: &[usize],
+        adjacency: &Adjacency<N, EDGES, NODES>,
+    ) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        self.check_complete(order, adjacency)?;
+        Ok(order.iter().map(|&idx| adjacency.node(idx)).collect())
+    }
+
+    /// Turn an incomplete order returned by [`Graph::kahn`] into `Error::Cycle`.
+    fn check_complete(
+        &self,
+        order: &[usize],
+        adjacency: &Adjacency<N, EDGES, NODES>,
+    ) -> Result<(), Error<N, EDGES>> {
+        if order.len() == adjacency.len() {
+            return Ok(());
+        }
+        let residual = self
+            .edges
+            .iter()
+            .filter(|edge| !adjacency.is_sorted(edge.from));
+        Err(Error::Cycle(find_cycle(residual)?))
+    }
+}
+
+/// Collect edges into a graph.
+///
+/// # Panics
+/// Panics if the iterator yields more than `EDGES` edges. Use [`Graph::try_from_iter`] for a fallible version.
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> FromIterator<Edge<N>>
+    for Graph<N, EDGES, NODES>
+{
+    fn from_iter<I: IntoIterator<Item = Edge<N>>>(iter: I) -> Self {
+        Self::try_from_iter(iter)
+            .unwrap_or_else(|_| panic!("Graph::from_iter: more than {} edges", EDGES))
+    }
+}
+
+/// Append edges to a graph.
+///
+/// `Extend` can't report failure, so edges beyond capacity are silently dropped.
+/// Use [`Graph::try_extend`] if you need to know.
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Extend<Edge<N>>
+    for Graph<N, EDGES, NODES>
+{
+    fn extend<I: IntoIterator<Item = Edge<N>>>(&mut self, iter: I) {
+        let _ = self.try_extend(iter);
+    }
+}
+
+/// Consume the graph, yielding its edges in insertion order.
+impl<N, const EDGES: usize, const NODES: usize> IntoIterator for Graph<N, EDGES, NODES> {
+    type Item = Edge<N>;
+    type IntoIter = IntoIter<N, EDGES>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        // reversed so `next` can pop from the back
+        self.edges.reverse();
+        IntoIter { edges: self.edges }
+    }
+}
+
+/// Owning iterator over a graph's edges, see [`Graph::into_iter`](IntoIterator::into_iter).
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Clone)]
+pub struct IntoIter<N, const EDGES: usize> {
+    edges: Vec<Edge<N>, EDGES>,
+}
+
+impl<N, const EDGES: usize> Iterator for IntoIter<N, EDGES> {
+    type Item = Edge<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.edges.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.edges.len(), Some(self.edges.len()))
+    }
+}
+
+impl<N, const EDGES: usize> ExactSizeIterator for IntoIter<N, EDGES> {}
+
+/// Iterate over the graph's edges in insertion order.
+impl<'a, N, const EDGES: usize, const NODES: usize> IntoIterator for &'a Graph<N, EDGES, NODES> {
+    type Item = &'a Edge<N>;
+    type IntoIter = core::slice::Iter<'a, Edge<N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.edges.iter()
+    }
+}
+
+/// Build a graph from an array of `(from, to)` pairs, see [`Graph::from_array`].
+/// Arrays longer than `EDGES` fail to compile.
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize, const LEN: usize>
+    From<[(N, N); LEN]> for Graph<N, EDGES, NODES>
+{
+    fn from(value: [(N, N); LEN]) -> Self {
+        Self::from_array(value)
+    }
+}
+
+/// Build a graph from a slice of `(from, to)` pairs.
+/// Returns `Error::OverCapacity` if the slice is longer than `EDGES`.
+///
+/// ```
+/// use heapless_topo::Graph;
+/// let edges: &[(usize, usize)] = &[(0, 1), (1, 2)][..];
+/// let graph = Graph::<usize, 4>::try_from(edges).ok().unwrap();
+/// let sorted = graph.into_topo_sorted().ok().unwrap();
+/// assert_eq!(&[0, 1, 2], sorted.as_slice());
+/// ```
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> TryFrom<&[(N, N)]>
+    for Graph<N, EDGES, NODES>
+{
+    type Error = Error<N, EDGES>;
+
+    fn try_from(value: &[(N, N)]) -> Result<Self, Self::Error> {
+        Self::try_from_iter(value.iter().copied())
+    }
+}
+
+/// Extract one cycle from the edges left over after Kahn's algorithm has terminated.
+///
+/// Every node with a leftover outgoing edge also has a leftover incoming edge (otherwise it would have
+/// been sorted), so walking edges *backwards* from any leftover edge must eventually revisit a node.
+fn find_cycle<'a, N: Copy + Eq + 'a, const EDGES: usize>(
+    edges: impl Iterator<Item = &'a Edge<N>> + Clone,
+) -> Result<Vec<N, EDGES>, Error<N, EDGES>> {
+    let mut cycle = Vec::new();
+    find_cycle_into(edges, &mut cycle).map_err(|_| Error::OverCapacity { needed: EDGES + 1 })?;
+    Ok(cycle)
+}
+
+/// [`find_cycle`] into any buffer, leaving the cycle in `path`. Shared with `DynGraph`.
+fn find_cycle_into<'a, N: Copy + Eq + 'a, B: Buffer<N>>(
+    edges: impl Iterator<Item = &'a Edge<N>> + Clone,
+    path: &mut B,
+) -> Result<(), N> {
+    path.clear();
+    let Some(first) = edges.clone().next() else {
+        return Ok(());
+    };
+    let mut node = first.from;
+    loop {
+        if let Some(pos) = path.iter().position(|&n| n == node) {
+            // drop the walk leading up to the cycle, then flip it back since it went against edge direction
+            path.rotate_left(pos);
+            let len = path.len() - pos;
+            path.truncate(len);
+            path.reverse();
+            return Ok(());
+        }
+        path.try_push(node)?;
+        match edges.clone().find(|edge| edge.to == node) {
+            Some(edge) => node = edge.from,
+            // can't happen after Kahn's algorithm terminated; report the path we have instead of nothing
+            None => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const CAPACITY: usize = 32;
+
+    #[test]
+    fn new_in_const_context() {
+        const EMPTY: Graph<u8, 16> = Graph::new();
+        static STATIC: Graph<u8, 16> = Graph::new();
+        assert!(EMPTY.is_empty());
+        assert!(STATIC == EMPTY);
+        let mut graph = EMPTY;
+        graph.insert_edge(Edge::new(0, 1)).unwrap();
+        assert_eq!([0, 1], graph.into_topo_sorted().unwrap().as_slice());
+    }
+
+    /// Assert that `sorted` contains every node of `edge_data` exactly once, and respects every edge.
+    fn assert_valid_topo_sort(edge_data: &[(usize, usize)], sorted: &[usize]) {
+        let position = |node| {
+            let mut positions = sorted.iter().enumerate().filter(|(_, &n)| n == node);
+            let (pos, _) = positions
+                .next()
+                .unwrap_or_else(|| panic!("node {node} missing from {sorted:?}"));
+            assert!(
+                positions.next().is_none(),
+                "node {node} duplicated in {sorted:?}"
+            );
+            pos
+        };
+        for &(from, to) in edge_data {
+            assert!(
+                position(from) < position(to),
+                "{sorted:?} violates {from} -> {to}"
+            );
+        }
+        for &node in sorted {
+            assert!(
+                edge_data
+                    .iter()
+                    .any(|&(from, to)| from == node || to == node),
+                "unknown node {node} in {sorted:?}"
+            );
+        }
+    }
+    #[test]
+    fn ok() {
+        // the first 4 edges imply the only possible topological sorting is 1,2,3,4,5
+        let edge_data = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)];
+        let mut edges: heapless::Vec<Edge<usize>, CAPACITY> = heapless::Vec::new();
+
+        // construct graph from a reversed iter to reduce likelihood of accidental success
+        for edge in edge_data.into_iter().rev() {
+            edges
+                .push(edge.into())
+                .expect("bug in test case: edge vec over capacity");
+        }
+        let graph = Graph::<usize, CAPACITY>::new_with_edges(edges);
+        let res = graph.into_topo_sorted();
+        let expected = [1, 2, 3, 4, 5].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), res);
+    }
+
+    #[test]
+    fn ok_with_push() {
+        // the first 4 edges imply the only possible topological sorting is 1,2,3,4,5
+        let edge_data = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)];
+        let mut graph = Graph::<usize, CAPACITY>::new();
+        for edge in edge_data.into_iter() {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        let res = graph.into_topo_sorted();
+        let expected = [1, 2, 3, 4, 5].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), res);
+    }
+
+    #[test]
+    fn ok_branching() {
+        // the first 4 edges imply the only possible topological sorting is 1,2,3,4,5
+        let edge_data = [(1, 2), (1, 3), (2, 4), (3, 4)];
+        let mut graph = Graph::<usize, CAPACITY>::new();
+        for edge in edge_data.into_iter() {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        let res = graph.into_topo_sorted();
+        // there is no unique toposort of this graph, so we can just assert ok
+        // if we don't want to rely on implementation details
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn ok_enum_nodes() {
+        #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+        enum Task {
+            Fetch,
+            Build,
+            Test,
+        }
+        let mut graph = Graph::<Task, 4>::new();
+        graph.insert_edge((Task::Build, Task::Test).into()).unwrap();
+        graph
+            .insert_edge((Task::Fetch, Task::Build).into())
+            .unwrap();
+        let expected = [Task::Fetch, Task::Build, Task::Test]
+            .as_slice()
+            .try_into()
+            .unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn ok_small_int_nodes() {
+        let mut graph = Graph::<u8, 16>::new();
+        graph.insert_edge((2, 3).into()).unwrap();
+        graph.insert_edge((1, 2).into()).unwrap();
+        let expected: Vec<u8, 16> = [1, 2, 3].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn edge_endpoints() {
+        let edge = Edge::new(3, 7);
+        assert_eq!(3, edge.source());
+        assert_eq!(7, edge.target());
+    }
+
+    #[test]
+    fn edge_ord() {
+        assert!(Edge::new(0, 5) < Edge::new(1, 0));
+        assert!(Edge::new(1, 0) < Edge::new(1, 2));
+        assert_eq!(Ordering::Equal, Edge::new(1, 2).cmp(&Edge::new(1, 2)));
+
+        let mut edges: Vec<Edge<u8>, 4> = [(2, 0), (0, 3), (2, 1), (0, 1)]
+            .into_iter()
+            .map(Edge::from)
+            .collect();
+        edges.sort();
+        let expected: Vec<Edge<u8>, 4> = [(0, 1), (0, 3), (2, 0), (2, 1)]
+            .into_iter()
+            .map(Edge::from)
+            .collect();
+        assert_eq!(expected, edges);
+    }
+
+    #[test]
+    fn reversed_edge() {
+        assert_eq!(Edge::new(7, 3), Edge::new(3, 7).reversed());
+    }
+
+    #[test]
+    fn ok_reverse() {
+        let mut graph = Graph::<usize, CAPACITY>::new();
+        for edge in [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)] {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        let res = graph.reverse().into_topo_sorted();
+        let expected = [5, 4, 3, 2, 1].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), res);
+    }
+
+    #[test]
+    fn ok_collect() {
+        let graph: Graph<usize, 4> = [(1, 2), (2, 3)].map(Edge::from).into_iter().collect();
+        let expected = [1, 2, 3].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    #[should_panic]
+    fn collect_over_capacity_panics() {
+        let _: Graph<usize, 1> = [(1, 2), (2, 3)].map(Edge::from).into_iter().collect();
+    }
+
+    #[test]
+    fn ok_try_from_iter() {
+        let graph = Graph::<usize, 4>::try_from_iter([(1, 2), (2, 3)]).unwrap();
+        let expected: Vec<usize, 4> = [1, 2, 3].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected.clone()), graph.into_topo_sorted());
+
+        let graph = Graph::<usize, 4>::try_from_iter([(1, 2), (2, 3)].map(Edge::from)).unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn err_try_from_iter_short_circuits() {
+        let mut iter = (0..10).map(|n| (n, n + 1));
+        let res = Graph::<usize, 2>::try_from_iter(iter.by_ref());
+        assert_eq!(Err(Error::OverCapacity { needed: 3 }), res);
+        // the third edge overflowed, the rest was not consumed
+        assert_eq!(Some((3, 4)), iter.next());
+    }
+
+    #[test]
+    fn extend_fits() {
+        let mut graph = Graph::<usize, 4>::new();
+        graph.insert_edge((1, 2).into()).unwrap();
+        graph.extend([(2, 3), (3, 4)].map(Edge::from));
+        let expected = [1, 2, 3, 4].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn extend_overflow_drops_excess() {
+        let mut graph = Graph::<usize, 2>::new();
+        graph.extend([(1, 2), (2, 3), (3, 4)].map(Edge::from));
+        assert_eq!(Graph::try_from_iter([(1, 2), (2, 3)]).unwrap(), graph);
+    }
+
+    #[test]
+    fn try_extend_fits() {
+        let mut graph = Graph::<usize, 4>::new();
+        assert_eq!(Ok(()), graph.try_extend([(1, 2), (2, 3)]));
+        let expected = [1, 2, 3].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn err_try_extend_overflow() {
+        let mut graph = Graph::<usize, 2>::new();
+        graph.insert_edge((1, 2).into()).unwrap();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.try_extend([(2, 3), (3, 4)])
+        );
+        // the edge that fit is kept
+        assert_eq!(Graph::try_from_iter([(1, 2), (2, 3)]).unwrap(), graph);
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let edges: &[(usize, usize)] = &[(0, 1), (1, 2), (2, 3)];
+        assert!(Graph::<usize, 4>::try_from(edges).is_ok());
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            Graph::<usize, 2>::try_from(edges)
+        );
+    }
+
+    #[test]
+    fn from_array() {
+        let graph = Graph::<usize, 2, 4>::from_array([(1, 2), (2, 3)]);
+        let expected = [1, 2, 3].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+
+        let graph: Graph<usize, 4> = Graph::from([(1, 2), (2, 3)]);
+        assert_eq!(Graph::try_from_iter([(1, 2), (2, 3)]).unwrap(), graph);
+    }
+
+    #[test]
+    fn remove_edge() {
+        let mut graph = Graph::<usize, 4>::from_array([(1, 2), (2, 3), (1, 2), (3, 4)]);
+        assert!(graph.remove_edge((1, 2).into()));
+        assert_eq!(Graph::from_array([(2, 3), (1, 2), (3, 4)]), graph);
+        assert!(graph.remove_edge((1, 2).into()));
+        assert!(!graph.remove_edge((1, 2).into()));
+        assert_eq!(Graph::from_array([(2, 3), (3, 4)]), graph);
+    }
+
+    #[test]
+    fn contains_edge() {
+        let graph = Graph::<usize, 4>::from_array([(1, 2), (2, 3)]);
+        assert!(graph.contains_edge((1, 2).into()));
+        assert!(!graph.contains_edge((2, 1).into()));
+    }
+
+    #[test]
+    fn insert_edge_unique() {
+        let mut graph = Graph::<usize, 1>::new();
+        assert_eq!(Ok(true), graph.insert_edge_unique((1, 2).into()));
+        assert_eq!(Ok(false), graph.insert_edge_unique((1, 2).into()));
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 2 }),
+            graph.insert_edge_unique((2, 3).into())
+        );
+    }
+
+    #[test]
+    fn insert_edge_at() {
+        let mut graph = Graph::<usize, 3>::new();
+        for (expected, edge) in [(0, 1), (1, 2), (0, 1)].into_iter().enumerate() {
+            assert_eq!(Ok(expected), graph.insert_edge_at(edge.into()));
+        }
+        assert_eq!(Some(Edge::new(1, 2)), graph.get_edge(1));
+        assert_eq!(None, graph.get_edge(3));
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 4 }),
+            graph.insert_edge_at(Edge::new(2, 3))
+        );
+    }
+
+    #[test]
+    fn insert_edges() {
+        let mut graph = Graph::<usize, 3>::from_array([(0, 1)]);
+        assert_eq!(Ok(()), graph.insert_edges([(1, 2)].map(Edge::from)));
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 4 }),
+            graph.insert_edges([(2, 3), (3, 4)].map(Edge::from))
+        );
+        // edges before the failing one are kept
+        assert_eq!(Graph::from_array([(0, 1), (1, 2), (2, 3)]), graph);
+    }
+
+    #[test]
+    fn node_count() {
+        assert_eq!(Ok(0), Graph::<usize, 4>::new().node_count());
+        let graph = Graph::<usize, 4>::from_array([(1, 2), (2, 3), (1, 3), (1, 2)]);
+        assert_eq!(Ok(3), graph.node_count());
+        let graph = Graph::<usize, 4, 2>::from_array([(1, 2), (2, 3)]);
+        assert_eq!(Err(Error::OverCapacity { needed: 3 }), graph.node_count());
+    }
+
+    #[test]
+    fn non_power_of_two_capacity() {
+        let graph = Graph::<usize, 6>::from_array([(4, 5), (3, 4), (2, 3), (1, 2), (0, 1)]);
+        let sorted = graph.into_topo_sorted().unwrap();
+        assert_eq!(&[0, 1, 2, 3, 4, 5], sorted.as_slice());
+
+        // every node slot in use
+        let mut graph = Graph::<usize, 3, 5>::from_array([(10, 20), (30, 40), (20, 50)]);
+        assert_eq!(Ok(5), graph.node_count());
+        let sorted = graph.topo_sorted().unwrap();
+        assert_eq!(&[10, 30, 20, 40, 50], sorted.as_slice());
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 6 }),
+            graph.add_node(60).and_then(|_| graph.into_topo_sorted())
+        );
+    }
+
+    #[test]
+    fn required_capacity() {
+        assert_eq!(0, Graph::<usize, 4>::new().required_capacity());
+        // more edges than nodes
+        let graph = Graph::<usize, 4>::from_array([(0, 1), (1, 0), (0, 1)]);
+        assert_eq!(3, graph.required_capacity());
+        // nodes beyond `NODES` are still counted
+        let mut graph = Graph::<usize, 4, 2>::from_array([(1, 2), (2, 3)]);
+        graph.edges.push(Edge::new(5, 6)).unwrap();
+        assert_eq!(5, graph.required_capacity());
+        // added nodes count as well
+        let mut graph = Graph::<usize, 4>::from_array([(0, 1), (1, 2)]);
+        graph.add_node(7).unwrap();
+        assert_eq!(4, graph.required_capacity());
+        assert_eq!(Ok(4), graph.into_topo_sorted().map(|sorted| sorted.len()));
+    }
+
+    #[test]
+    fn edge_count() {
+        let mut graph = Graph::<usize, 4>::new();
+        assert!(graph.is_empty());
+        assert_eq!(0, graph.len());
+        graph.insert_edge((1, 2).into()).unwrap();
+        graph.insert_edge((1, 2).into()).unwrap();
+        assert!(!graph.is_empty());
+        assert_eq!(2, graph.edge_count());
+        assert_eq!(2, graph.len());
+    }
+
+    #[test]
+    fn clear_and_reuse() {
+        let mut graph = Graph::<usize, 4>::from_array([(3, 4), (4, 5)]);
+        graph.clear();
+        assert!(graph.is_empty());
+        graph
+            .insert_edges([(1, 2), (0, 1)].map(Edge::from))
+            .unwrap();
+        let expected = [0, 1, 2].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn capacity() {
+        let mut graph = Graph::<usize, 4>::new();
+        assert_eq!(4, graph.capacity());
+        assert_eq!(4, graph.remaining_capacity());
+        graph
+            .insert_edges([(1, 2), (2, 3)].map(Edge::from))
+            .unwrap();
+        assert_eq!(4, graph.capacity());
+        assert_eq!(2, graph.remaining_capacity());
+    }
+
+    #[test]
+    fn degrees() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (1, 3), (2, 4), (3, 4), (1, 2)]);
+        assert_eq!(0, graph.in_degree(1));
+        assert_eq!(3, graph.out_degree(1));
+        assert_eq!(2, graph.in_degree(2));
+        assert_eq!(2, graph.in_degree(4));
+        assert_eq!(0, graph.out_degree(4));
+        assert_eq!(0, graph.in_degree(42));
+        assert_eq!(0, graph.out_degree(42));
+        assert_eq!(3, graph.degree(1));
+        assert_eq!(3, graph.degree(2));
+        assert_eq!(0, graph.degree(42));
+        let graph = Graph::<usize, 8>::from_array([(0, 0), (0, 1)]);
+        assert_eq!(3, graph.degree(0));
+    }
+
+    #[test]
+    fn roots() {
+        let graph = Graph::<usize, 8>::from_array([(5, 2), (1, 2), (5, 3), (2, 4), (3, 4), (0, 1)]);
+        let mut roots = graph.roots();
+        assert_eq!(Some(5), roots.next());
+        assert_eq!(Some(0), roots.next());
+        assert_eq!(None, roots.next());
+        assert_eq!(
+            None,
+            Graph::<usize, 8>::from_array([(1, 2), (2, 1)])
+                .roots()
+                .next()
+        );
+
+        // isolated nodes come last, added nodes with edges aren't repeated
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1)]);
+        graph.add_node(7).unwrap();
+        graph.add_node(0).unwrap();
+        graph.add_node(1).unwrap();
+        assert!(graph.roots().eq([0, 7]));
+        assert!(graph.leaves().eq([1, 7]));
+    }
+
+    #[test]
+    fn is_source_sink() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (3, 3)]);
+        graph.add_node(9).unwrap();
+        assert!(graph.is_source(0));
+        assert!(!graph.is_sink(0));
+        assert!(!graph.is_source(1));
+        assert!(!graph.is_sink(1));
+        assert!(!graph.is_source(2));
+        assert!(graph.is_sink(2));
+        // self-loops count both ways
+        assert!(!graph.is_source(3));
+        assert!(!graph.is_sink(3));
+        // isolated nodes are both, unknown ones neither, just like for roots and leaves
+        assert!(graph.is_source(9));
+        assert!(graph.is_sink(9));
+        assert!(!graph.is_source(42));
+        assert!(!graph.is_sink(42));
+        for node in [0, 1, 2, 3, 9, 42] {
+            assert_eq!(graph.roots().any(|n| n == node), graph.is_source(node));
+            assert_eq!(graph.leaves().any(|n| n == node), graph.is_sink(node));
+        }
+    }
+
+    #[test]
+    fn leaves() {
+        // the crate docs example
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (0, 1)]);
+        let mut leaves = graph.leaves();
+        assert_eq!(Some(2), leaves.next());
+        assert_eq!(None, leaves.next());
+
+        let graph =
+            Graph::<usize, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5), (2, 6)]);
+        let mut leaves = graph.leaves();
+        assert_eq!(Some(5), leaves.next());
+        assert_eq!(Some(6), leaves.next());
+        assert_eq!(None, leaves.next());
+    }
+
+    #[test]
+    fn successors_predecessors() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (1, 3), (2, 4), (3, 4), (1, 2)]);
+        let mut successors = graph.successors(1);
+        assert_eq!(Some(2), successors.next());
+        assert_eq!(Some(3), successors.next());
+        assert_eq!(Some(2), successors.next());
+        assert_eq!(None, successors.next());
+        let mut predecessors = graph.predecessors(4);
+        assert_eq!(Some(2), predecessors.next());
+        assert_eq!(Some(3), predecessors.next());
+        assert_eq!(None, predecessors.next());
+        assert_eq!(None, graph.predecessors(1).next());
+    }
+
+    #[test]
+    fn nodes() {
+        let graph = Graph::<usize, 8>::from_array([(5, 2), (2, 5), (7, 7), (2, 4), (3, 4)]);
+        let mut nodes = graph.nodes();
+        for expected in [5, 2, 7, 4, 3] {
+            assert_eq!(Some(expected), nodes.next());
+        }
+        assert_eq!(None, nodes.next());
+        assert_eq!(None, Graph::<usize, 8>::new().nodes().next());
+    }
+
+    #[test]
+    fn iterate_edges() {
+        let edge_data = [(1, 2), (0, 1), (1, 2)].map(Edge::from);
+        let graph = Graph::<usize, 4>::from_array([(1, 2), (0, 1), (1, 2)]);
+        assert_eq!(&edge_data, graph.edges());
+        for (edge, expected) in (&graph).into_iter().zip(&edge_data) {
+            assert_eq!(expected, edge);
+        }
+        let mut count = 0;
+        for (edge, expected) in graph.into_iter().zip(edge_data) {
+            assert_eq!(expected, edge);
+            count += 1;
+        }
+        assert_eq!(3, count);
+    }
+
+    #[test]
+    fn ok_isolated_nodes() {
+        let mut graph = Graph::<usize, 4>::from_array([(1, 2)]);
+        graph.add_node(7).unwrap();
+        graph.add_node(7).unwrap();
+        // already implied by an edge, must not show up twice
+        graph.add_node(1).unwrap();
+        assert_eq!(Ok(3), graph.node_count());
+        let mut nodes = graph.nodes();
+        for expected in [1, 2, 7] {
+            assert_eq!(Some(expected), nodes.next());
+        }
+        assert_eq!(None, nodes.next());
+        drop(nodes);
+
+        let mut sorted = graph.into_topo_sorted().unwrap();
+        sorted.sort_unstable();
+        assert_eq!(&[1, 2, 7], sorted.as_slice());
+    }
+
+    #[test]
+    fn ok_only_isolated_nodes() {
+        let mut graph = Graph::<usize, 4>::new();
+        graph.add_node(3).unwrap();
+        let expected = [3].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn err_too_many_nodes() {
+        let mut graph = Graph::<usize, 4, 2>::new();
+        assert_eq!(Ok(()), graph.add_node(1));
+        assert_eq!(Ok(()), graph.add_node(2));
+        assert_eq!(Err(Error::OverCapacity { needed: 3 }), graph.add_node(3));
+    }
+
+    #[test]
+    fn ok_single_edge_keeps_both_endpoints() {
+        let graph = Graph::<usize, 4>::from_array([(0, 1)]);
+        let expected = [0, 1].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn ok_every_node_exactly_once() {
+        let graphs: [&[(usize, usize)]; 6] = [
+            &[(0, 1)],
+            // 3 and 4 are only ever targets
+            &[(0, 3), (1, 3), (2, 4), (0, 4)],
+            &[(1, 2), (1, 2), (0, 2)],
+            &[(0, 2), (1, 2), (1, 2)],
+            &[(0, 1), (1, 2), (0, 2), (2, 3), (1, 3), (0, 3)],
+            &[(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2)],
+        ];
+        for edge_data in graphs {
+            // every rotation of the edge list, to shake out order dependence
+            for rotation in 0..edge_data.len() {
+                let mut graph = Graph::<usize, 8>::new();
+                for &edge in edge_data[rotation..].iter().chain(&edge_data[..rotation]) {
+                    graph.insert_edge(edge.into()).unwrap();
+                }
+                let sorted = graph.into_topo_sorted().unwrap();
+                assert_valid_topo_sort(edge_data, &sorted);
+            }
+        }
+    }
+
+    #[test]
+    fn ok_long_chain() {
+        // a chain inserted back to front is the worst case for edge-scanning implementations,
+        // which needed O(nodes * edges²) here
+        fn chain<const LEN: usize>() {
+            let mut graph = Graph::<usize, LEN>::new();
+            for node in (0..LEN - 1).rev() {
+                graph.insert_edge((node, node + 1).into()).unwrap();
+            }
+            let sorted = graph.into_topo_sorted().unwrap();
+            assert!(sorted.iter().copied().eq(0..LEN));
+        }
+        chain::<32>();
+        chain::<1024>();
+    }
+
+    #[test]
+    fn topo_sorted_keeps_graph() {
+        let mut graph = Graph::<usize, 8>::from_array([(1, 2), (0, 1)]);
+        let copy = graph.clone();
+        let expected: Vec<usize, 8> = [0, 1, 2].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected.clone()), graph.topo_sorted());
+        assert_eq!(copy, graph);
+        assert_eq!(Ok(expected), graph.topo_sorted());
+        assert_eq!(copy, graph);
+
+        graph.insert_edge((2, 0).into()).unwrap();
+        assert!(matches!(graph.topo_sorted(), Err(Error::Cycle(_))));
+    }
+
+    #[test]
+    fn topo_sort_into() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (0, 1)]);
+        let mut out: Vec<usize, 3> = [7, 7].as_slice().try_into().unwrap();
+        assert_eq!(Ok(()), graph.topo_sort_into(&mut out));
+        assert_eq!(&[0, 1, 2], out.as_slice());
+
+        let mut out: Vec<usize, 2> = Vec::new();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.topo_sort_into(&mut out)
+        );
+
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (2, 1)]);
+        let mut out: Vec<usize, 3> = [7].as_slice().try_into().unwrap();
+        assert!(matches!(
+            graph.topo_sort_into(&mut out),
+            Err(Error::Cycle(_))
+        ));
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn ok_rev() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)]);
+        let expected = [5, 4, 3, 2, 1].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted_rev());
+
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (1, 3), (2, 4), (3, 4)]);
+        let mut forward = graph.topo_sorted().unwrap();
+        forward.reverse();
+        assert_eq!(Ok(forward), graph.into_topo_sorted_rev());
+    }
+
+    #[test]
+    fn ok_lex() {
+        // valid orders include [3, 2, 1, 0, 4] and [0, 1, 2, 3, 4]; 4 must come after 3 and 0
+        let graph = Graph::<usize, 8>::from_array([(3, 4), (2, 4), (0, 4), (3, 1)]);
+        let expected: Vec<usize, 8> = [0, 2, 3, 1, 4].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected.clone()), graph.into_topo_sorted_lex());
+
+        // insertion order doesn't matter
+        let graph = Graph::<usize, 8>::from_array([(3, 1), (0, 4), (2, 4), (3, 4)]);
+        assert_eq!(Ok(expected), graph.into_topo_sorted_lex());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        assert!(matches!(graph.into_topo_sorted_lex(), Err(Error::Cycle(_))));
+    }
+
+    #[test]
+    fn ok_deterministic_default_order() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (1, 3), (2, 4), (3, 4)]);
+        let expected = [1, 2, 3, 4].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+
+        // ties are broken by insertion order, not by node id
+        let graph = Graph::<usize, 8>::from_array([(1, 3), (1, 2), (3, 4), (2, 4)]);
+        let expected = [1, 3, 2, 4].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+
+        let graph = Graph::<usize, 8>::from_array([(9, 4), (0, 4), (5, 9)]);
+        let expected = [0, 5, 9, 4].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn ok_stable() {
+        let graph = Graph::<usize, 8>::from_array([(3, 0), (1, 2), (3, 2)]);
+        let stable = [3, 0, 1, 2].as_slice().try_into().unwrap();
+        let default = [3, 1, 0, 2].as_slice().try_into().unwrap();
+        let lex = [1, 3, 0, 2].as_slice().try_into().unwrap();
+        assert_eq!(Ok(stable), graph.clone().into_topo_sorted_stable());
+        assert_eq!(Ok(default), graph.clone().into_topo_sorted());
+        assert_eq!(Ok(lex), graph.into_topo_sorted_lex());
+    }
+
+    #[test]
+    fn ok_sorted_by() {
+        // prefer larger nodes
+        let graph = Graph::<usize, 8>::from_array([(3, 4), (2, 4), (0, 4), (3, 1)]);
+        let expected = [3, 2, 1, 0, 4].as_slice().try_into().unwrap();
+        assert_eq!(
+            Ok(expected),
+            graph.clone().into_topo_sorted_by(|a, b| b.cmp(&a))
+        );
+
+        // prioritize by external weights, only ever comparing ready nodes
+        let weights = [5, 1, 3, 2, 0];
+        let mut calls = 0;
+        let res = graph.into_topo_sorted_by(|a, b| {
+            calls += 1;
+            assert_ne!(4, a, "4 is never ready alongside another node");
+            assert_ne!(4, b, "4 is never ready alongside another node");
+            weights[a].cmp(&weights[b])
+        });
+        let expected = [3, 1, 2, 0, 4].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), res);
+        assert!(calls > 0);
+    }
+
+    #[test]
+    fn ok_dfs() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)]);
+        let expected = [1, 2, 3, 4, 5].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted_dfs());
+
+        // chains stay together: 0 -> 1 -> 2 and 3 -> 4 -> 5
+        let edge_data = [(0, 1), (3, 4), (1, 2), (4, 5)];
+        let graph = Graph::<usize, 8>::from_array(edge_data);
+        let sorted = graph.into_topo_sorted_dfs().unwrap();
+        assert_eq!(&[3, 4, 5, 0, 1, 2], sorted.as_slice());
+
+        for edge_data in [
+            [(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2)],
+            [(0, 1), (1, 2), (0, 2), (2, 3), (1, 3), (0, 3)],
+        ] {
+            let sorted = Graph::<usize, 8>::from_array(edge_data)
+                .into_topo_sorted_dfs()
+                .unwrap();
+            assert_valid_topo_sort(&edge_data, &sorted);
+        }
+    }
+
+    #[test]
+    fn err_dfs_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)]);
+        let expected = [1, 2, 3].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.into_topo_sorted_dfs());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 0)]);
+        let expected = [0].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.into_topo_sorted_dfs());
+    }
+
+    #[test]
+    fn ok_levels() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        let levels = graph.into_levels().unwrap();
+        assert_eq!(3, levels.len());
+        assert_eq!(&[0], levels[0].as_slice());
+        assert_eq!(&[1, 2], levels[1].as_slice());
+        assert_eq!(&[3], levels[2].as_slice());
+
+        // a node is only ready once its longest chain of predecessors is done
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (0, 2), (3, 2)]);
+        graph.add_node(4).unwrap();
+        let levels = graph.into_levels().unwrap();
+        assert_eq!(3, levels.len());
+        assert_eq!(&[4, 0, 3], levels[0].as_slice());
+        assert_eq!(&[1], levels[1].as_slice());
+        assert_eq!(&[2], levels[2].as_slice());
+
+        assert!(Graph::<usize, 8>::new().into_levels().unwrap().is_empty());
+    }
+
+    #[test]
+    fn err_levels_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        let expected = [2, 1].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.into_levels());
+    }
+
+    #[test]
+    fn ok_frontiers() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        let frontiers = graph.clone().into_frontiers().unwrap();
+        let expected: [&[usize]; 4] = [&[0], &[1, 2], &[2], &[3]];
+        assert!(frontiers.iter().map(Vec::as_slice).eq(expected));
+        let heads = frontiers.iter().map(|frontier| frontier[0]);
+        assert!(heads.eq(graph.into_topo_sorted().unwrap()));
+
+        assert!(Graph::<usize, 8>::new()
+            .into_frontiers()
+            .unwrap()
+            .is_empty());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        let expected = [2, 1].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.into_frontiers());
+    }
+
+    #[test]
+    fn ranks() {
+        let edge_data = [(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2)];
+        let mut graph = Graph::<usize, 8>::from_array(edge_data);
+        graph.add_node(9).unwrap();
+        let sorted = graph.clone().into_topo_sorted().unwrap();
+        let ranks = graph.ranks().unwrap();
+        assert_eq!(sorted.len(), ranks.len());
+        for (rank, &node) in sorted.iter().enumerate() {
+            assert_eq!(Some(rank), ranks.get(node));
+        }
+        // first appearance order
+        assert!(ranks.iter().map(|(node, _)| node).eq([9, 5, 0, 4, 1, 3, 2]));
+        assert!(Graph::<usize, 8>::new().ranks().unwrap().is_empty());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        let expected = [2, 1].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.ranks().map(|_| ()));
+    }
+
+    #[test]
+    fn depths() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        graph.add_node(9).unwrap();
+        let depths = graph.depths().unwrap();
+        assert_eq!(Some(0), depths.get(0));
+        assert_eq!(Some(1), depths.get(1));
+        assert_eq!(Some(1), depths.get(2));
+        assert_eq!(Some(2), depths.get(3));
+        assert_eq!(Some(0), depths.get(9));
+        assert_eq!(None, depths.get(4));
+
+        // the longer branch wins
+        let graph = Graph::<usize, 8>::from_array([(0, 3), (0, 1), (1, 2), (2, 3)]);
+        assert_eq!(Some(3), graph.depths().unwrap().get(3));
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        assert!(matches!(graph.depths(), Err(Error::Cycle(_))));
+    }
+
+    #[test]
+    fn heights() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3), (0, 4)]);
+        graph.add_node(9).unwrap();
+        let heights = graph.clone().heights().unwrap();
+        assert_eq!(Some(2), heights.get(0));
+        assert_eq!(Some(1), heights.get(1));
+        assert_eq!(Some(1), heights.get(2));
+        assert_eq!(Some(0), heights.get(3));
+        assert_eq!(Some(0), heights.get(4));
+        assert_eq!(Some(0), heights.get(9));
+        assert_eq!(None, heights.get(5));
+
+        // slack: only 4 and the isolated 9 are off the critical path
+        let len = graph.clone().critical_path_len().unwrap();
+        let depths = graph.depths().unwrap();
+        for (node, height) in heights.iter() {
+            let slack = len - depths.get(node).unwrap() - height;
+            assert_eq!(node == 4 || node == 9, slack > 0);
+        }
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        assert!(matches!(graph.heights(), Err(Error::Cycle(_))));
+    }
+
+    #[test]
+    fn ok_max_width() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        assert_eq!(Ok(2), graph.max_width());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(Ok(1), graph.max_width());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 4), (1, 4), (2, 4), (3, 4)]);
+        assert_eq!(Ok(4), graph.max_width());
+
+        assert_eq!(Ok(0), Graph::<usize, 8>::new().max_width());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        assert!(matches!(graph.max_width(), Err(Error::Cycle(_))));
+    }
+
+    #[test]
+    fn ok_all_topo_sorts() {
+        let edge_data = [(0, 1), (0, 2), (1, 3), (2, 3)];
+        let graph = Graph::<usize, 8>::from_array(edge_data);
+        let mut orders: Vec<Vec<usize, 8>, 8> = Vec::new();
+        graph
+            .all_topo_sorts(|order| orders.push(order.try_into().unwrap()).unwrap())
+            .unwrap();
+        assert_eq!(2, orders.len());
+        assert_eq!(&[0, 1, 2, 3], orders[0].as_slice());
+        assert_eq!(&[0, 2, 1, 3], orders[1].as_slice());
+
+        // two independent chains of two: 4! / (2! * 2!) interleavings, all distinct and valid
+        let edge_data = [(0, 1), (2, 3)];
+        let graph = Graph::<usize, 8>::from_array(edge_data);
+        let mut orders: Vec<Vec<usize, 8>, 16> = Vec::new();
+        graph
+            .all_topo_sorts(|order| {
+                assert_valid_topo_sort(&edge_data, order);
+                assert!(!orders.iter().any(|other| other == order));
+                orders.push(order.try_into().unwrap()).unwrap();
+            })
+            .unwrap();
+        assert_eq!(6, orders.len());
+
+        let mut count = 0;
+        Graph::<usize, 8>::new()
+            .all_topo_sorts(|order| {
+                assert!(order.is_empty());
+                count += 1;
+            })
+            .unwrap();
+        assert_eq!(1, count);
+    }
+
+    #[test]
+    fn err_all_topo_sorts_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        let expected = [2, 1].as_slice().try_into().unwrap();
+        let result = graph.all_topo_sorts(|_| panic!("cyclic graphs have no order"));
+        assert_eq!(Err(Error::Cycle(expected)), result);
+    }
+
+    #[test]
+    fn ok_count_topo_sorts() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        assert_eq!(Ok(2), graph.count_topo_sorts());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (0, 3)]);
+        assert_eq!(Ok(1), graph.count_topo_sorts());
+
+        let mut graph = Graph::<usize, 8>::new();
+        for node in 0..5 {
+            graph.add_node(node).unwrap();
+        }
+        assert_eq!(Ok(120), graph.count_topo_sorts());
+
+        assert_eq!(Ok(1), Graph::<usize, 8>::new().count_topo_sorts());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        assert!(matches!(graph.count_topo_sorts(), Err(Error::Cycle(_))));
+    }
+
+    #[test]
+    fn ok_has_unique_order() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (0, 3)]);
+        assert_eq!(Ok(true), graph.has_unique_order());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3)]);
+        assert_eq!(Ok(false), graph.has_unique_order());
+
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1)]);
+        graph.add_node(2).unwrap();
+        assert_eq!(Ok(false), graph.has_unique_order());
+
+        assert_eq!(Ok(true), Graph::<usize, 8>::new().has_unique_order());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        assert!(matches!(graph.has_unique_order(), Err(Error::Cycle(_))));
+    }
+
+    #[test]
+    fn ok_critical_path() {
+        let edge_data = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)];
+        let graph = Graph::<usize, 8>::from_array(edge_data);
+        assert_eq!(Ok(4), graph.clone().critical_path_len());
+        assert_eq!(&[1, 2, 3, 4, 5], graph.critical_path().unwrap().as_slice());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (2, 3), (1, 4), (4, 5), (6, 5)]);
+        assert_eq!(Ok(3), graph.clone().critical_path_len());
+        assert_eq!(&[0, 1, 4, 5], graph.critical_path().unwrap().as_slice());
+
+        let mut graph = Graph::<usize, 8>::new();
+        assert_eq!(Ok(0), graph.clone().critical_path_len());
+        assert!(graph.clone().critical_path().unwrap().is_empty());
+        graph.add_node(7).unwrap();
+        assert_eq!(Ok(0), graph.clone().critical_path_len());
+        assert_eq!(&[7], graph.critical_path().unwrap().as_slice());
+    }
+
+    #[test]
+    fn err_critical_path_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        let expected: Vec<usize, 8> = [2, 1].as_slice().try_into().unwrap();
+        assert_eq!(
+            Err(Error::Cycle(expected.clone())),
+            graph.clone().critical_path_len()
+        );
+        assert_eq!(Err(Error::Cycle(expected)), graph.critical_path());
+    }
+
+    #[test]
+    fn ok_transitive_closure() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3), (4, 3)]);
+        let closure = graph.transitive_closure::<16>().unwrap();
+        let expected = [(0, 1), (0, 2), (0, 3), (1, 3), (2, 3), (4, 3)];
+        assert_eq!(expected.len(), closure.edge_count());
+        for edge in expected {
+            assert!(closure.contains_edge(edge.into()), "missing {edge:?}");
+        }
+        assert_eq!(
+            &[0, 4, 1, 2, 3],
+            closure.into_topo_sorted().unwrap().as_slice()
+        );
+
+        // nodes on a cycle reach themselves
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        let closure = graph.transitive_closure::<16>().unwrap();
+        assert_eq!(
+            &[
+                Edge::new(0, 1),
+                Edge::new(0, 2),
+                Edge::new(1, 2),
+                Edge::new(1, 1),
+                Edge::new(2, 1),
+                Edge::new(2, 2)
+            ],
+            closure.edges()
+        );
+
+        let mut graph = Graph::<usize, 8>::new();
+        graph.add_node(3).unwrap();
+        let closure = graph.transitive_closure::<8>().unwrap();
+        assert!(closure.is_empty());
+        assert_eq!(&[3], closure.into_topo_sorted().unwrap().as_slice());
+    }
+
+    #[test]
+    fn err_transitive_closure_over_capacity() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (3, 4)]);
+        assert_eq!(
+            Ok(10),
+            graph
+                .clone()
+                .transitive_closure::<16>()
+                .map(|closure| closure.edge_count())
+        );
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 10 }),
+            graph.transitive_closure::<8>()
+        );
+    }
+
+    #[test]
+    fn ok_transitive_reduction() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3), (0, 3), (1, 3)]);
+        let reduced = graph.transitive_reduction().unwrap();
+        assert_eq!(
+            &[
+                Edge::new(0, 1),
+                Edge::new(0, 2),
+                Edge::new(1, 3),
+                Edge::new(2, 3)
+            ],
+            reduced.edges()
+        );
+
+        // reducing the closure gives back the original graph
+        let edge_data = [(0, 1), (1, 2), (2, 3), (4, 2)];
+        let graph = Graph::<usize, 8>::from_array(edge_data);
+        let closure = graph.clone().transitive_closure::<8>().unwrap();
+        let reduced = closure.transitive_reduction().unwrap();
+        assert_eq!(graph.edge_count(), reduced.edge_count());
+        for edge in edge_data {
+            assert!(reduced.contains_edge(edge.into()), "missing {edge:?}");
+        }
+
+        let mut graph = Graph::<usize, 8>::new();
+        graph.add_node(0).unwrap();
+        let reduced = graph.clone().transitive_reduction().unwrap();
+        assert_eq!(graph, reduced);
+    }
+
+    #[test]
+    fn err_transitive_reduction_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        let expected = [2, 1].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.transitive_reduction());
+    }
+
+    #[test]
+    fn reachable() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (4, 3), (5, 6), (6, 5)]);
+        assert!(graph.reachable(0, 1));
+        assert!(graph.reachable(0, 3));
+        assert!(graph.reachable(4, 3));
+        assert!(!graph.reachable(3, 0));
+        assert!(!graph.reachable(0, 4));
+        assert!(!graph.reachable(0, 7));
+        // only nodes on a cycle reach themselves
+        assert!(!graph.reachable(0, 0));
+        assert!(graph.reachable(5, 5));
+        assert!(graph.reachable(6, 5));
+
+        let graph = Graph::<usize, 8>::from_array([(0, 0)]);
+        assert!(graph.reachable(0, 0));
+        assert!(!Graph::<usize, 8>::new().reachable(0, 0));
+    }
+
+    #[test]
+    fn is_dag() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)]);
+        assert!(graph.is_dag());
+        let graph =
+            Graph::<usize, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5), (5, 1)]);
+        assert!(!graph.is_dag());
+
+        // diamonds revisit nodes without being cyclic
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3), (3, 4)]);
+        assert!(graph.is_dag());
+        let graph = Graph::<usize, 8>::from_array([(3, 4), (0, 1), (0, 2), (1, 3), (2, 3), (4, 2)]);
+        assert!(!graph.is_dag());
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 1)]);
+        assert!(!graph.is_dag());
+        assert!(Graph::<usize, 8>::new().is_dag());
+    }
+
+    #[test]
+    fn find_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)]);
+        assert_eq!(None, graph.find_cycle());
+        let graph =
+            Graph::<usize, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5), (5, 1)]);
+        assert_eq!(Some(&[1, 2, 3, 4, 5][..]), graph.find_cycle().as_deref());
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 1)]);
+        assert_eq!(Some(&[1][..]), graph.find_cycle().as_deref());
+
+        let graph = Graph::<usize, 8>::from_array([(3, 4), (0, 1), (0, 2), (1, 3), (2, 3), (4, 2)]);
+        let cycle = graph.find_cycle().unwrap();
+        assert_eq!(3, cycle.len());
+        for (idx, &from) in cycle.iter().enumerate() {
+            let to = cycle[(idx + 1) % cycle.len()];
+            assert!(graph.contains_edge(Edge::new(from, to)), "{cycle:?}");
+        }
+    }
+
+    /// Collect the cycles of `graph` into a sorted list, for comparison.
+    fn all_cycles(graph: Graph<usize, 16>) -> std::vec::Vec<std::vec::Vec<usize>> {
+        let mut cycles = std::vec::Vec::new();
+        graph
+            .all_cycles(|cycle| cycles.push(cycle.to_vec()))
+            .unwrap();
+        cycles.sort();
+        cycles
+    }
+
+    #[test]
+    fn all_cycles_overlapping() {
+        // two triangles sharing the edge (1, 2), plus the cycle around both
+        let graph = Graph::from_array([(0, 1), (1, 2), (2, 0), (2, 3), (3, 1), (3, 0)]);
+        assert_eq!(
+            vec![vec![0, 1, 2], vec![0, 1, 2, 3], vec![1, 2, 3]],
+            all_cycles(graph)
+        );
+
+        // self-loops and parallel edges
+        let graph = Graph::from_array([(0, 1), (1, 0), (1, 0), (1, 1), (2, 3)]);
+        assert_eq!(vec![vec![0, 1], vec![1]], all_cycles(graph));
+
+        assert!(all_cycles(Graph::from_array([(0, 1), (1, 2), (0, 2)])).is_empty());
+    }
+
+    #[test]
+    fn all_cycles_complete() {
+        // every ordered pair of 4 nodes: C(4, k) * (k - 1)! cycles of each length k >= 2
+        let mut graph = Graph::<usize, 16>::new();
+        for from in 0..4 {
+            for to in (0..4).filter(|&to| to != from) {
+                graph.insert_edge(Edge::new(from, to)).unwrap();
+            }
+        }
+        let cycles = all_cycles(graph);
+        assert_eq!(6 + 4 * 2 + 6, cycles.len());
+        for cycle in &cycles {
+            // elementary and starting at the smallest node
+            let mut sorted = cycle.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(cycle.len(), sorted.len());
+            assert_eq!(sorted[0], cycle[0]);
+        }
+    }
+
+    #[test]
+    fn self_loops() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (3, 3), (1, 2), (1, 1), (3, 3)]);
+        let mut out: Vec<usize, 4> = Vec::new();
+        graph.self_loops(&mut out).unwrap();
+        assert_eq!(&[3, 1], out.as_slice());
+
+        let mut out: Vec<usize, 1> = Vec::new();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 2 }),
+            graph.self_loops(&mut out)
+        );
+        assert_eq!(&[3], out.as_slice());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        graph.self_loops(&mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn parallel_edges() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (1, 2), (0, 1), (1, 2)]);
+        assert!(graph.has_parallel_edges());
+        let mut out: Vec<Edge<usize>, 4> = Vec::new();
+        graph.parallel_edges(&mut out).unwrap();
+        assert_eq!(&[Edge::new(0, 1), Edge::new(1, 2)], out.as_slice());
+
+        let mut out: Vec<Edge<usize>, 1> = Vec::new();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 2 }),
+            graph.parallel_edges(&mut out)
+        );
+        assert_eq!(&[Edge::new(0, 1)], out.as_slice());
+
+        // opposite directions aren't parallel
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        assert!(!graph.has_parallel_edges());
+        graph.parallel_edges(&mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn would_create_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (3, 2)]);
+        assert!(graph.would_create_cycle(Edge::new(2, 0)));
+        assert!(graph.would_create_cycle(Edge::new(1, 0)));
+        assert!(graph.would_create_cycle(Edge::new(4, 4)));
+        assert!(!graph.would_create_cycle(Edge::new(0, 2)));
+        assert!(graph.would_create_cycle(Edge::new(2, 3)));
+        assert!(!graph.would_create_cycle(Edge::new(0, 3)));
+        assert!(!graph.would_create_cycle(Edge::new(2, 4)));
+    }
+
+    #[test]
+    fn try_insert_edge() {
+        let mut graph = Graph::<usize, 4>::new();
+        assert_eq!(Ok(()), graph.try_insert_edge(Edge::new(0, 1)));
+        assert_eq!(Ok(()), graph.try_insert_edge(Edge::new(1, 2)));
+        assert_eq!(Ok(()), graph.try_insert_edge(Edge::new(0, 2)));
+        assert_eq!(
+            &[Edge::new(0, 1), Edge::new(1, 2), Edge::new(0, 2)],
+            graph.edges()
+        );
+        assert_eq!(&[0, 1, 2], graph.topo_sorted().unwrap().as_slice());
+    }
+
+    #[test]
+    fn err_try_insert_edge() {
+        let mut graph = Graph::<usize, 3>::from_array([(0, 1), (1, 2)]);
+        let before = graph.clone();
+        assert_eq!(
+            Err(Error::WouldCycle),
+            graph.try_insert_edge(Edge::new(2, 0))
+        );
+        assert_eq!(Err(Error::SelfLoop), graph.try_insert_edge(Edge::new(1, 1)));
+        assert_eq!(before, graph);
+
+        graph.try_insert_edge(Edge::new(2, 3)).unwrap();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 4 }),
+            graph.try_insert_edge(Edge::new(3, 4))
+        );
+        assert_eq!(3, graph.edge_count());
+    }
+
+    #[test]
+    fn ancestors_descendants() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 2), (1, 3), (2, 3), (3, 4), (5, 2)]);
+        let mut out: Vec<usize, 8> = Vec::new();
+        graph.descendants(0, &mut out).unwrap();
+        assert_eq!(&[1, 2, 3, 4], out.as_slice());
+        graph.descendants(4, &mut out).unwrap();
+        assert!(out.is_empty());
+        graph.ancestors(3, &mut out).unwrap();
+        assert_eq!(&[1, 2, 0, 5], out.as_slice());
+        graph.ancestors(5, &mut out).unwrap();
+        assert!(out.is_empty());
+        graph.descendants(7, &mut out).unwrap();
+        assert!(out.is_empty());
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        graph.descendants(1, &mut out).unwrap();
+        assert_eq!(&[2, 1], out.as_slice());
+        graph.ancestors(1, &mut out).unwrap();
+        assert_eq!(&[0, 2, 1], out.as_slice());
+    }
+
+    #[test]
+    fn err_descendants_over_capacity() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3)]);
+        let mut out: Vec<usize, 2> = Vec::new();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.descendants(0, &mut out)
+        );
+        assert_eq!(&[1, 2], out.as_slice());
+    }
+
+    #[test]
+    fn merge() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2)]);
+        let mut other = Graph::<usize, 4>::from_array([(3, 4), (1, 3), (0, 1)]);
+        other.add_node(5).unwrap();
+        graph.merge(&other).unwrap();
+        assert_eq!(5, graph.edge_count());
+        let sorted = graph.clone().into_topo_sorted().unwrap();
+        assert_eq!(&[5, 0, 1, 2, 3, 4], sorted.as_slice());
+
+        let mut unique = Graph::<usize, 8>::from_array([(0, 1), (1, 2)]);
+        other.insert_edge(Edge::new(3, 4)).unwrap();
+        assert_eq!(Ok(2), unique.merge_unique(&other));
+        assert_eq!(
+            &[
+                Edge::new(0, 1),
+                Edge::new(1, 2),
+                Edge::new(3, 4),
+                Edge::new(1, 3)
+            ],
+            unique.edges()
+        );
+        assert_eq!(Ok(0), unique.merge_unique(&other));
+        assert_eq!(sorted, unique.into_topo_sorted().unwrap());
+    }
+
+    #[test]
+    fn err_merge_over_capacity() {
+        let mut graph = Graph::<usize, 4>::from_array([(0, 1), (1, 2)]);
+        let other = Graph::<usize, 4>::from_array([(2, 3), (3, 4), (4, 5)]);
+        let before = graph.clone();
+        assert_eq!(Err(Error::OverCapacity { needed: 5 }), graph.merge(&other));
+        assert_eq!(before, graph);
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 5 }),
+            graph.merge_unique(&other)
+        );
+        assert_eq!(before, graph);
+
+        // duplicates don't count
+        let other = Graph::<usize, 4>::from_array([(0, 1), (2, 3), (2, 3), (1, 2)]);
+        assert_eq!(Ok(1), graph.merge_unique(&other));
+        assert_eq!(3, graph.edge_count());
+
+        let mut graph = Graph::<usize, 4, 2>::new();
+        graph.add_node(0).unwrap();
+        let mut other = Graph::<usize, 4, 2>::new();
+        other.add_node(1).unwrap();
+        other.add_node(2).unwrap();
+        assert_eq!(Err(Error::OverCapacity { needed: 3 }), graph.merge(&other));
+        assert_eq!(1, graph.node_count().unwrap());
+    }
+
+    #[test]
+    fn subgraph() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (0, 3), (4, 0)]);
+        graph.add_node(5).unwrap();
+        let subgraph = graph.subgraph(&[0, 1, 3, 4, 5, 6]).unwrap();
+        assert_eq!(
+            &[Edge::new(0, 1), Edge::new(0, 3), Edge::new(4, 0)],
+            subgraph.edges()
+        );
+        let sorted = subgraph.into_topo_sorted().unwrap();
+        assert_eq!(&[5, 4, 0, 1, 3], sorted.as_slice());
+
+        // 2 loses all its edges but stays part of the subgraph
+        let subgraph = graph.subgraph_by(|node| node >= 2).unwrap();
+        assert_eq!(&[Edge::new(2, 3)], subgraph.edges());
+        let sorted = subgraph.into_topo_sorted().unwrap();
+        assert_eq!(&[4, 5, 2, 3], sorted.as_slice());
+
+        assert!(graph.subgraph(&[]).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let edge = Edge::new(1, 2);
+        assert_eq!("[1,2]", serde_json::to_string(&edge).unwrap());
+        assert_eq!(edge, serde_json::from_str("[1,2]").unwrap());
+
+        let mut graph = Graph::<usize, 4>::from_array([(0, 1), (1, 2)]);
+        graph.add_node(3).unwrap();
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(r#"{"edges":[[0,1],[1,2]],"nodes":[3]}"#, json);
+        assert_eq!(graph, serde_json::from_str(&json).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn err_serde_over_capacity() {
+        let json = r#"{"edges":[[0,1],[1,2],[2,3]],"nodes":[]}"#;
+        assert!(serde_json::from_str::<Graph<usize, 2>>(json).is_err());
+        assert!(serde_json::from_str::<Graph<usize, 4>>(json).is_ok());
+    }
+
+    #[test]
+    fn error_display() {
+        let cycle: Error<usize, 4> = Error::Cycle([0, 1].as_slice().try_into().unwrap());
+        assert_eq!("cycle detected", cycle.to_string());
+        assert_eq!(
+            "capacity exceeded, needs at least 5",
+            Error::<usize, 4>::OverCapacity { needed: 5 }.to_string()
+        );
+        assert_eq!(
+            "edge would create a cycle",
+            Error::<usize, 4>::WouldCycle.to_string()
+        );
+        assert_eq!(
+            "edge is a self-loop",
+            Error::<usize, 4>::SelfLoop.to_string()
+        );
+        assert_eq!(
+            "duplicate edge",
+            Error::<usize, 4>::DuplicateEdge.to_string()
+        );
+
+        let boxed: Box<dyn core::error::Error> = Box::new(cycle);
+        assert_eq!("cycle detected", boxed.to_string());
+    }
+
+    #[test]
+    fn ok_duplicate_edges() {
+        for edge_data in [
+            [(1, 2), (1, 2), (0, 2), (3, 4)],
+            [(0, 2), (1, 2), (1, 2), (3, 4)],
+            // 2 must wait for 3 even though both edges from 1 are gone
+            [(1, 2), (1, 2), (0, 3), (3, 2)],
+            [(0, 3), (3, 2), (1, 2), (1, 2)],
+        ] {
+            let graph = Graph::<usize, 8>::from_array(edge_data);
+            for sorted in [
+                graph.clone().into_topo_sorted().unwrap(),
+                graph.clone().into_topo_sorted_stable().unwrap(),
+                graph.clone().into_topo_sorted_lex().unwrap(),
+                graph.clone().into_topo_sorted_dfs().unwrap(),
+                graph.clone().into_topo_sorted_by(|a, b| b.cmp(&a)).unwrap(),
+            ] {
+                assert_valid_topo_sort(&edge_data, &sorted);
+            }
+            let iterated: Vec<usize, 8> = graph.topo_iter().map(Result::unwrap).collect();
+            assert_valid_topo_sort(&edge_data, &iterated);
+        }
+
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (1, 2), (0, 2)]);
+        let expected = [1, 0, 2].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn err_duplicate_edges_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (0, 1), (1, 0)]);
+        let expected = [1, 0].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn insert_edge_strict() {
+        let mut graph = Graph::<usize, 2>::new();
+        assert_eq!(Ok(()), graph.insert_edge_strict(Edge::new(0, 1)));
+        assert_eq!(
+            Err(Error::DuplicateEdge),
+            graph.insert_edge_strict(Edge::new(0, 1))
+        );
+        assert_eq!(Ok(()), graph.insert_edge_strict(Edge::new(1, 0)));
+        assert_eq!(
+            Err(Error::DuplicateEdge),
+            graph.insert_edge_strict(Edge::new(1, 0))
+        );
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.insert_edge_strict(Edge::new(1, 2))
+        );
+        assert_eq!(&[Edge::new(0, 1), Edge::new(1, 0)], graph.edges());
+    }
+
+    #[test]
+    fn eq_unordered() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (0, 1)]);
+        let mut other = Graph::<usize, 8>::from_array([(1, 2), (0, 1), (0, 1)]);
+        assert!(graph != other);
+        assert!(graph.eq_unordered(&other));
+        assert!(other.eq_unordered(&graph));
+
+        // same edge set, but a different number of duplicates
+        let fewer = Graph::<usize, 8>::from_array([(1, 2), (1, 2), (0, 1)]);
+        assert!(!graph.eq_unordered(&fewer));
+        assert!(!fewer.eq_unordered(&graph));
+
+        other.add_node(3).unwrap();
+        assert!(!graph.eq_unordered(&other));
+        let mut graph = graph;
+        graph.add_node(3).unwrap();
+        assert!(graph.eq_unordered(&other));
+    }
+
+    #[test]
+    fn map_nodes() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (0, 2)]);
+        graph.add_node(3).unwrap();
+        let shifted = graph.clone().map_nodes(|n| n + 10);
+        assert_eq!(
+            &[Edge::new(10, 11), Edge::new(11, 12), Edge::new(10, 12)],
+            shifted.edges()
+        );
+        assert_eq!(
+            &[13, 10, 11, 12],
+            shifted.into_topo_sorted().unwrap().as_slice()
+        );
+
+        // collapsing 1 and 2 creates a self-loop and a duplicate edge
+        let narrowed: Graph<u8, 8> = graph.map_nodes(|n| n.min(1) as u8);
+        assert_eq!(
+            &[Edge::new(0, 1), Edge::new(1, 1), Edge::new(0, 1)],
+            narrowed.edges()
+        );
+        assert!(!narrowed.is_dag());
+    }
+
+    #[test]
+    fn min_max_node_id() {
+        let mut graph = Graph::<usize, 8>::new();
+        assert_eq!(None, graph.max_node_id());
+        assert_eq!(None, graph.min_node_id());
+        graph.add_node(7).unwrap();
+        assert_eq!(Some(7), graph.max_node_id());
+        assert_eq!(Some(7), graph.min_node_id());
+        graph.insert_edge(Edge::new(12, 3)).unwrap();
+        graph.insert_edge(Edge::new(5, 9)).unwrap();
+        assert_eq!(Some(12), graph.max_node_id());
+        assert_eq!(Some(3), graph.min_node_id());
+    }
+
+    #[test]
+    fn canonicalize() {
+        use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+        let hash = |graph: &Graph<usize, 8>| {
+            BuildHasherDefault::<DefaultHasher>::default().hash_one(graph)
+        };
+
+        let mut graph = Graph::<usize, 8>::from_array([(2, 3), (0, 1), (1, 2), (0, 1)]);
+        graph.add_node(5).unwrap();
+        graph.add_node(4).unwrap();
+        let mut other = Graph::<usize, 8>::from_array([(0, 1), (0, 1), (1, 2), (2, 3)]);
+        other.add_node(4).unwrap();
+        other.add_node(5).unwrap();
+        assert!(graph != other);
+
+        graph.canonicalize();
+        other.canonicalize();
+        assert!(graph == other);
+        assert_eq!(hash(&graph), hash(&other));
+        assert_eq!(
+            &[
+                Edge::new(0, 1),
+                Edge::new(0, 1),
+                Edge::new(1, 2),
+                Edge::new(2, 3)
+            ],
+            graph.edges()
+        );
+        assert_eq!(
+            &[4, 5, 0, 1, 2, 3],
+            graph.into_topo_sorted().unwrap().as_slice()
+        );
+    }
+
+    #[test]
+    fn retain_edges() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (3, 4), (1, 2), (4, 0), (2, 5)]);
+        graph.add_node(7).unwrap();
+        graph.retain_edges(|edge| edge.from < 3);
+        assert_eq!(
+            &[Edge::new(0, 1), Edge::new(1, 2), Edge::new(2, 5)],
+            graph.edges()
+        );
+        assert_eq!(
+            &[7, 0, 1, 2, 5],
+            graph.into_topo_sorted().unwrap().as_slice()
+        );
+    }
+
+    #[test]
+    fn remove_node() {
+        let mut graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 3), (1, 3), (1, 1)]);
+        graph.add_node(1).unwrap();
+        graph.add_node(4).unwrap();
+        assert_eq!(4, graph.remove_node(1));
+        assert_eq!(&[Edge::new(2, 3)], graph.edges());
+        // 0 was only referenced by an edge to 1
+        assert_eq!(
+            &[4, 2, 3],
+            graph.clone().into_topo_sorted().unwrap().as_slice()
+        );
+
+        assert_eq!(0, graph.remove_node(4));
+        assert_eq!(0, graph.remove_node(5));
+        assert_eq!(&[2, 3], graph.into_topo_sorted().unwrap().as_slice());
+    }
+
+    #[test]
+    fn err_too_many_edges() {
+        let mut graph = Graph::<usize, 1>::new();
+        assert_eq!(Ok(()), graph.insert_edge((1, 2).into()));
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 2 }),
+            graph.insert_edge((2, 3).into())
+        );
+    }
+
+    #[test]
+    fn err_num_nodes_greater_than_num_edges() {
+        let mut graph = Graph::<usize, 2>::new();
+        assert_eq!(Ok(()), graph.insert_edge((1, 2).into()));
+        assert_eq!(Ok(()), graph.insert_edge((0, 1).into()));
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.into_topo_sorted()
+        );
+    }
+
+    #[test]
+    fn ok_num_nodes_greater_than_num_edges() {
+        let mut graph = Graph::<usize, 2, 4>::new();
+        assert_eq!(Ok(()), graph.insert_edge((1, 2).into()));
+        assert_eq!(Ok(()), graph.insert_edge((0, 1).into()));
+        let expected = [0, 1, 2].as_slice().try_into().unwrap();
+        assert_eq!(Ok(expected), graph.into_topo_sorted());
+    }
+
+    #[test]
+    fn err_cycles() {
+        let edge_data = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5), (5, 1)];
+        let mut edges: heapless::Vec<Edge<usize>, CAPACITY> = heapless::Vec::new();
+
+        for edge in edge_data {
+            edges
+                .push(edge.into())
+                .expect("bug in test case: edge vec over capacity");
+        }
+        let graph = Graph::<usize, CAPACITY>::new_with_edges(edges);
+        let res = graph.into_topo_sorted();
+        let expected = [2, 3, 4, 5, 1].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), res);
+    }
+
+    #[test]
+    fn err_cycle_reports_only_cycle_nodes() {
+        // 0 and 4 are not part of the cycle 1 -> 2 -> 3 -> 1
+        let edge_data = [(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)];
+        let mut graph = Graph::<usize, CAPACITY>::new();
+        for edge in edge_data {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        let Err(Error::Cycle(cycle)) = graph.into_topo_sorted() else {
+            panic!("expected a cycle");
+        };
+        let mut sorted = cycle.clone();
+        sorted.sort_unstable();
+        assert_eq!(&[1, 2, 3], sorted.as_slice());
+        // consecutive nodes must be connected by an edge, including the wraparound
+        for (idx, &from) in cycle.iter().enumerate() {
+            let to = cycle[(idx + 1) % cycle.len()];
+            assert!(edge_data.contains(&(from, to)));
+        }
+    }
+
+    #[test]
+    fn partial_acyclic() {
+        let edge_data = [(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)];
+        let mut graph = Graph::<usize, CAPACITY>::new();
+        for edge in edge_data {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        let (sorted, residual) = graph.into_topo_sorted_partial().unwrap();
+        assert_eq!(&[1, 2, 3, 4, 5], sorted.as_slice());
+        assert!(residual.is_empty());
+    }
+
+    #[test]
+    fn partial_cyclic() {
+        // 0 -> 1 is sortable, 1 -> 2 -> 3 -> 1 is a knot and 3 -> 4 hangs off it
+        let edge_data = [(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)];
+        let mut graph = Graph::<usize, CAPACITY>::new();
+        for edge in edge_data {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        let (sorted, residual) = graph.into_topo_sorted_partial().unwrap();
+        assert_eq!(&[0], sorted.as_slice());
+        let expected: [Edge<usize>; 4] = [(1, 2), (2, 3), (3, 1), (3, 4)].map(Edge::from);
+        assert_eq!(&expected, residual.as_slice());
+    }
+
+    #[test]
+    fn err_self_loop_cycle() {
+        let mut graph = Graph::<usize, CAPACITY>::new();
+        graph.insert_edge((1, 1).into()).unwrap();
+        let expected = [1].as_slice().try_into().unwrap();
+        assert_eq!(Err(Error::Cycle(expected)), graph.into_topo_sorted());
     }
 }
@@ -0,0 +1,116 @@
+//! Conversion from and to boolean adjacency matrices, see [`Graph::from_adjacency_matrix`] and
+//! [`Graph::to_adjacency_matrix`].
+
+use crate::{Edge, Error, Graph};
+
+impl<const EDGES: usize, const NODES: usize> Graph<usize, EDGES, NODES> {
+    /// Create a graph with an edge `(i, j)` for every `matrix[i][j] == true`, in row-major order.
+    ///
+    /// Only nodes with at least one edge become part of the graph; use [`Graph::add_node`] to register isolated
+    /// ones. Returns `Error::OverCapacity` with the number of set cells if there are more than `EDGES`.
+    ///
+    /// ```
+    /// use heapless_topo::Graph;
+    /// let matrix = [
+    ///     [false, false, false],
+    ///     [true, false, false],
+    ///     [true, true, false],
+    /// ];
+    /// let graph = Graph::<usize, 4>::from_adjacency_matrix(&matrix).ok().unwrap();
+    /// let sorted = graph.into_topo_sorted().ok().unwrap();
+    /// assert_eq!(&[2, 1, 0], sorted.as_slice());
+    /// ```
+    pub fn from_adjacency_matrix<const SIZE: usize>(
+        matrix: &[[bool; SIZE]; SIZE],
+    ) -> Result<Self, Error<usize, EDGES>> {
+        let needed = matrix.iter().flatten().filter(|&&cell| cell).count();
+        if needed > EDGES {
+            return Err(Error::OverCapacity { needed });
+        }
+        let mut graph = Self::new();
+        for (from, row) in matrix.iter().enumerate() {
+            for (to, _) in row.iter().enumerate().filter(|(_, &cell)| cell) {
+                // can't fail: checked above
+                graph.insert_edge(Edge::new(from, to))?;
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Create a matrix with `matrix[i][j] == true` for every edge `(i, j)`, e.g. for matrix-based numeric code.
+    ///
+    /// Node ids are used as indices, so every node id (including nodes added via [`Graph::add_node`]) has to be
+    /// below `SIZE`; otherwise returns `Error::OverCapacity` with the `SIZE` that would be needed, i.e. the largest
+    /// node id plus one. Parallel edges collapse into one cell, and isolated nodes leave no trace, so
+    /// [`Graph::from_adjacency_matrix`] restores the graph only up to those.
+    pub fn to_adjacency_matrix<const SIZE: usize>(
+        &self,
+    ) -> Result<[[bool; SIZE]; SIZE], Error<usize, EDGES>> {
+        if let Some(max) = self.max_node_id().filter(|&max| max >= SIZE) {
+            return Err(Error::OverCapacity { needed: max + 1 });
+        }
+        let mut matrix = [[false; SIZE]; SIZE];
+        for edge in &self.edges {
+            matrix[edge.from][edge.to] = true;
+        }
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_adjacency_matrix() {
+        let matrix = [
+            [false, true, true],
+            [false, false, true],
+            [false, false, false],
+        ];
+        let graph = Graph::<usize, 4>::from_adjacency_matrix(&matrix).unwrap();
+        assert_eq!(
+            &[Edge::new(0, 1), Edge::new(0, 2), Edge::new(1, 2)],
+            graph.edges()
+        );
+        assert_eq!(&[0, 1, 2], graph.into_topo_sorted().unwrap().as_slice());
+
+        let graph = Graph::<usize, 4>::from_adjacency_matrix(&[[false; 3]; 3]).unwrap();
+        assert!(graph.is_empty());
+    }
+
+    #[test]
+    fn adjacency_matrix_round_trip() {
+        let matrix = [
+            [false, true, false, true],
+            [false, false, false, false],
+            [true, true, false, false],
+            [false, true, false, true],
+        ];
+        let graph = Graph::<usize, 8>::from_adjacency_matrix(&matrix).unwrap();
+        assert_eq!(Ok(matrix), graph.to_adjacency_matrix());
+
+        let mut graph = Graph::<usize, 8>::from_array([(0, 2), (0, 2)]);
+        graph.add_node(1).unwrap();
+        let expected = [[false, false, true], [false; 3], [false; 3]];
+        assert_eq!(Ok(expected), graph.to_adjacency_matrix());
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.to_adjacency_matrix::<2>()
+        );
+        graph.add_node(5).unwrap();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 6 }),
+            graph.to_adjacency_matrix::<3>()
+        );
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let matrix = [[true; 3]; 3];
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 9 }),
+            Graph::<usize, 8>::from_adjacency_matrix(&matrix)
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Insertion-ordered node set used to assign dense indices, see [`NodeIndex`].
+
+use core::{
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+use heapless::Vec;
+
+/// Marks an unused hash table slot (in both halves of a wide slot).
+const EMPTY: u16 = u16::MAX;
+
+/// Maps node ids to dense indices `0..len()` in order of insertion, and derefs to the nodes by dense index.
+///
+/// Like a `heapless::FnvIndexSet`, but `NODES` doesn't need to be a power of two: hashes are mapped onto the slots
+/// by a multiply-shift instead of masking, and collisions are resolved by linear probing. The table takes four bytes
+/// per node, independent of `N` and the pointer width:
+/// - up to `NODES = 65534`, that's `2 * NODES` slots of 16 bits. The table is never more than half full, so lookups
+///   and inserts take `O(1)` expected time, and the `O(nodes + edges)` bounds of the algorithms built on top hold
+///   in expectation.
+/// - beyond that, it's `NODES` slots of 32 bits. Close to capacity, probe chains then grow towards `O(NODES)`, and
+///   a miss on a full table visits every slot.
+///
+/// Adversarial ids that all collide degrade each probe to `O(NODES)` either way, like in any hash table.
+///
+/// There's deliberately no `defmt::Format` impl: the slots mean nothing outside the lookup code and would only
+/// bloat the log. Public types holding a `NodeIndex` (directly or via `Adjacency`) implement `Format` by hand
+/// instead and log the nodes, or just their progress.
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Clone)]
+pub(crate) struct NodeIndex<N, const NODES: usize> {
+    /// dense index -> node id
+    nodes: Vec<N, NODES>,
+    /// dense index of the node hashed to each slot, or `EMPTY`. Two halves since `[_; 2 * NODES]` isn't
+    /// expressible on stable, used as one flat table.
+    slots: [[u16; NODES]; 2],
+}
+
+enum Probe {
+    Found(usize),
+    Vacant(usize),
+    Full,
+}
+
+impl<N: Copy + Eq + Hash, const NODES: usize> NodeIndex<N, NODES> {
+    /// Whether dense indices don't fit into 16 bits next to `EMPTY`, so that every slot takes two `u16`s.
+    const WIDE: bool = NODES >= EMPTY as usize;
+    /// Number of hash table slots.
+    const SLOTS: usize = if Self::WIDE { NODES } else { 2 * NODES };
+
+    pub(crate) const fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            slots: [[EMPTY; NODES]; 2],
+        }
+    }
+
+    /// Remove all nodes. Takes `O(NODES)` time, independent of the number of nodes.
+    pub(crate) fn clear(&mut self) {
+        self.nodes.clear();
+        self.slots = [[EMPTY; NODES]; 2];
+    }
+
+    /// Dense index of `node`, if present.
+    pub(crate) fn index_of(&self, node: N) -> Option<usize> {
+        match self.probe(node) {
+            Probe::Found(idx) => Some(idx),
+            Probe::Vacant(_) | Probe::Full => None,
+        }
+    }
+
+    /// Dense index of `node`, assigning the next one if it's new. Returns `None` if there are already `NODES` nodes.
+    pub(crate) fn insert(&mut self, node: N) -> Option<usize> {
+        match self.probe(node) {
+            Probe::Found(idx) => Some(idx),
+            Probe::Vacant(slot) => {
+                let idx = self.nodes.len();
+                self.nodes.push(node).ok()?;
+                self.set_slot(slot, idx);
+                Some(idx)
+            }
+            Probe::Full => None,
+        }
+    }
+
+    fn probe(&self, node: N) -> Probe {
+        if Self::SLOTS == 0 {
+            return Probe::Full;
+        }
+        // maps the 32-bit hash onto `0..SLOTS` without a division
+        let start = ((u64::from(fnv1a(&node)) * Self::SLOTS as u64) >> 32) as usize;
+        for slot in (start..Self::SLOTS).chain(0..start) {
+            match self.slot(slot) {
+                None => return Probe::Vacant(slot),
+                Some(idx) if self.nodes[idx] == node => return Probe::Found(idx),
+                Some(_) => {}
+            }
+        }
+        Probe::Full
+    }
+
+    fn slot(&self, slot: usize) -> Option<usize> {
+        let slots = self.slots.as_flattened();
+        if Self::WIDE {
+            let idx = u32::from(slots[2 * slot]) | u32::from(slots[2 * slot + 1]) << 16;
+            (idx != u32::MAX).then_some(idx as usize)
+        } else {
+            (slots[slot] != EMPTY).then_some(usize::from(slots[slot]))
+        }
+    }
+
+    fn set_slot(&mut self, slot: usize, idx: usize) {
+        let slots = self.slots.as_flattened_mut();
+        // the truncations are lossless: `idx < NODES`, which fits in 16 bits unless `WIDE`
+        if Self::WIDE {
+            slots[2 * slot] = idx as u16;
+            slots[2 * slot + 1] = (idx >> 16) as u16;
+        } else {
+            slots[slot] = idx as u16;
+        }
+    }
+}
+
+impl<N, const NODES: usize> Deref for NodeIndex<N, NODES> {
+    type Target = [N];
+
+    fn deref(&self) -> &[N] {
+        &self.nodes
+    }
+}
+
+/// 32-bit FNV-1a hash of `node`, the same hash `heapless::FnvIndexSet` uses. Shared with `DynGraph`.
+pub(crate) fn fnv1a<N: Hash>(node: &N) -> u32 {
+    let mut hasher = Fnv1a::default();
+    node.hash(&mut hasher);
+    hasher.0
+}
+
+struct Fnv1a(u32);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0x811c_9dc5)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u32::from(byte)).wrapping_mul(0x0100_0193);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        u64::from(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insertion_order() {
+        let mut index = NodeIndex::<u32, 6>::new();
+        for (expected, node) in [(0, 40), (1, 7), (0, 40), (2, 0), (1, 7)] {
+            assert_eq!(Some(expected), index.insert(node));
+        }
+        assert_eq!(&[40, 7, 0], &*index);
+        assert_eq!(Some(1), index.index_of(7));
+        assert_eq!(None, index.index_of(8));
+
+        index.clear();
+        assert!(index.is_empty());
+        assert_eq!(None, index.index_of(7));
+        assert_eq!(Some(0), index.insert(7));
+    }
+
+    #[test]
+    fn full() {
+        // colliding and non-colliding ids alike, until `NODES` is reached
+        let mut index = NodeIndex::<u32, 3>::new();
+        for node in [3, 6, 9] {
+            assert!(index.insert(node).is_some());
+        }
+        assert_eq!(None, index.insert(1));
+        assert_eq!(None, index.index_of(1));
+        assert_eq!(Some(2), index.insert(9));
+
+        // a full index still finds every node, and misses still terminate
+        let mut index = NodeIndex::<u32, 7>::new();
+        for node in 0..7 {
+            assert_eq!(Some(node as usize), index.insert(node * 1000));
+        }
+        for node in 0..7 {
+            assert_eq!(Some(node as usize), index.index_of(node * 1000));
+        }
+        assert_eq!(None, index.index_of(1));
+        assert_eq!(None, index.insert(1));
+
+        let mut index = NodeIndex::<u32, 0>::new();
+        assert_eq!(None, index.insert(0));
+        assert_eq!(None, index.index_of(0));
+    }
+
+    #[test]
+    fn wide_slots() {
+        // too many nodes for 16-bit slots, so the table fills up completely
+        let mut index = NodeIndex::<u32, 70_000>::new();
+        for node in 0..70_000 {
+            assert_eq!(Some(node as usize), index.insert(node * 3));
+        }
+        assert_eq!(Some(69_999), index.index_of(69_999 * 3));
+        assert_eq!(Some(65_536), index.index_of(65_536 * 3));
+        assert_eq!(None, index.index_of(1));
+        assert_eq!(None, index.insert(1));
+    }
+}
@@ -0,0 +1,64 @@
+//! Per-node results like [`Graph::ranks`], see [`NodeMap`].
+
+use core::hash::Hash;
+
+use heapless::Vec;
+
+use crate::node_index::NodeIndex;
+#[cfg(doc)]
+use crate::Graph;
+
+/// A value for every node of a graph, e.g. its position in the sorted order as returned by [`Graph::ranks`].
+///
+/// Lookups by node id take `O(1)` time on average, like with a `heapless::FnvIndexMap`, but `NODES` doesn't need
+/// to be a power of two. Iteration yields the nodes in order of first appearance (see the crate docs on ordering).
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Clone)]
+pub struct NodeMap<N, V, const NODES: usize> {
+    /// node id <-> dense index
+    nodes: NodeIndex<N, NODES>,
+    /// dense index -> value
+    values: Vec<V, NODES>,
+}
+
+// by hand, see `NodeIndex`
+#[cfg(feature = "defmt-03")]
+impl<N: defmt::Format, V: defmt::Format, const NODES: usize> defmt::Format
+    for NodeMap<N, V, NODES>
+{
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "NodeMap {{ nodes: {}, values: {} }}",
+            &*self.nodes,
+            self.values
+        )
+    }
+}
+
+impl<N: Copy + Eq + Hash, V: Copy, const NODES: usize> NodeMap<N, V, NODES> {
+    /// `values` has to hold one value per node, by dense index.
+    pub(crate) fn new(nodes: NodeIndex<N, NODES>, values: Vec<V, NODES>) -> Self {
+        debug_assert_eq!(nodes.len(), values.len());
+        Self { nodes, values }
+    }
+
+    /// The value of `node`, if it is part of the graph.
+    pub fn get(&self, node: N) -> Option<V> {
+        self.nodes.index_of(node).map(|idx| self.values[idx])
+    }
+
+    /// All nodes with their values, in order of first appearance.
+    pub fn iter(&self) -> impl Iterator<Item = (N, V)> + '_ {
+        self.nodes.iter().copied().zip(self.values.iter().copied())
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
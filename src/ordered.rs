@@ -0,0 +1,301 @@
+//! Graph that keeps a topological order up to date while edges are inserted, see [`OrderedGraph`].
+
+use core::hash::Hash;
+
+use heapless::Vec;
+
+use crate::{node_index::NodeIndex, Edge, Error, Graph};
+
+/// A [`Graph`] together with a topological order of its nodes that is updated on every insertion, e.g. for an
+/// interactive editor that needs a valid order after each change.
+///
+/// Insertions that would create a cycle are rejected, so the graph is always acyclic and [`OrderedGraph::order`] is
+/// always valid. New nodes are appended to the order. An edge that already agrees with the order costs nothing
+/// beyond the insertion; one that points backwards only reorders the nodes between its endpoints: the ones
+/// reachable from its target move behind the others, keeping their relative order. This takes
+/// `O(window * edges)`, where `window` is the distance of the endpoints in the order, instead of a full re-sort.
+///
+/// Because of that, the order generally differs from what [`Graph::into_topo_sorted`] would return for the same
+/// graph, but it is a valid topological order all the same.
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[derive(Clone)]
+pub struct OrderedGraph<N, const EDGES: usize, const NODES: usize = EDGES> {
+    graph: Graph<N, EDGES, NODES>,
+    /// node id <-> dense id
+    nodes: NodeIndex<N, NODES>,
+    /// dense id -> position in `order`
+    positions: Vec<usize, NODES>,
+    order: Vec<N, NODES>,
+}
+
+// by hand, see `NodeIndex`
+#[cfg(feature = "defmt-03")]
+impl<N: defmt::Format, const EDGES: usize, const NODES: usize> defmt::Format
+    for OrderedGraph<N, EDGES, NODES>
+{
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "OrderedGraph {{ graph: {}, order: {} }}",
+            self.graph,
+            self.order
+        )
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Default
+    for OrderedGraph<N, EDGES, NODES>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> OrderedGraph<N, EDGES, NODES> {
+    pub const fn new() -> Self {
+        Self {
+            graph: Graph::new(),
+            nodes: NodeIndex::new(),
+            positions: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+
+    /// Start from an existing graph, ordered by [`Graph::topo_sorted`]. Fails like that does, e.g. with
+    /// `Error::Cycle` if the graph isn't acyclic.
+    pub fn from_graph(graph: Graph<N, EDGES, NODES>) -> Result<Self, Error<N, EDGES>> {
+        let order = graph.topo_sorted()?;
+        let mut nodes = NodeIndex::new();
+        for &node in &order {
+            // can't fail: `order` holds at most NODES distinct nodes
+            let _ = nodes.insert(node);
+        }
+        Ok(Self {
+            graph,
+            nodes,
+            positions: (0..order.len()).collect(),
+            order,
+        })
+    }
+
+    /// The current topological order: every node comes before all of its successors.
+    pub fn order(&self) -> &[N] {
+        &self.order
+    }
+
+    /// The underlying graph, for read-only queries.
+    pub fn graph(&self) -> &Graph<N, EDGES, NODES> {
+        &self.graph
+    }
+
+    /// Unwrap the underlying graph.
+    pub fn into_graph(self) -> Graph<N, EDGES, NODES> {
+        self.graph
+    }
+
+    /// Register a node, see [`Graph::add_node`]. New nodes are appended to the order.
+    /// Returns `Error::OverCapacity` if there are already `NODES` nodes.
+    pub fn add_node(&mut self, node: N) -> Result<(), Error<N, EDGES>> {
+        if self.nodes.index_of(node).is_none() && self.nodes.len() == NODES {
+            return Err(Error::OverCapacity { needed: NODES + 1 });
+        }
+        self.graph.add_node(node)?;
+        self.position_or_append(node);
+        Ok(())
+    }
+
+    /// Insert an edge and update the order, see [`OrderedGraph`].
+    ///
+    /// Like [`Graph::try_insert_edge`], returns `Error::OverCapacity` if the edge or its new endpoints don't fit,
+    /// `Error::SelfLoop` for an edge from a node to itself, and `Error::WouldCycle` if the edge would close a longer
+    /// cycle. The graph and the order are unchanged in all of these cases.
+    pub fn insert_edge(&mut self, edge: Edge<N>) -> Result<(), Error<N, EDGES>> {
+        if self.graph.remaining_capacity() == 0 {
+            return Err(Error::OverCapacity { needed: EDGES + 1 });
+        }
+        if edge.from == edge.to {
+            return Err(Error::SelfLoop);
+        }
+        let new_nodes = [edge.from, edge.to]
+            .iter()
+            .filter(|&&node| self.nodes.index_of(node).is_none())
+            .count();
+        let needed = self.nodes.len() + new_nodes;
+        if needed > NODES {
+            return Err(Error::OverCapacity { needed });
+        }
+        // a new node has no edges and is appended, so it can't be part of a cycle: if `to` is new, the edge agrees
+        // with the order, and if only `from` is new, it isn't reachable from `to`
+        let from = self.position_or_append(edge.from);
+        let to = self.position_or_append(edge.to);
+        if from > to && !self.reorder(to, from) {
+            return Err(Error::WouldCycle);
+        }
+        // can't fail: checked above
+        self.graph.insert_edge(edge)
+    }
+
+    /// Position of `node` in the order, appending it if it's new. The caller has to make sure there is room.
+    fn position_or_append(&mut self, node: N) -> usize {
+        // unwrap safety: the caller checked the capacity
+        let idx = self.nodes.insert(node).unwrap();
+        if idx == self.positions.len() {
+            // can't fail: all three have capacity NODES
+            let _ = self.positions.push(self.order.len());
+            let _ = self.order.push(node);
+        }
+        self.positions[idx]
+    }
+
+    fn position(&self, node: N) -> usize {
+        // unwrap safety: only called for endpoints of stored edges
+        self.positions[self.nodes.index_of(node).unwrap()]
+    }
+
+    /// Make room for an edge from `order[hi]` to `order[lo]` by moving everything in `order[lo..=hi]` that is
+    /// reachable from `order[lo]` behind the rest. Returns `false` without changes if `order[hi]` is reachable,
+    /// i.e. the edge would close a cycle.
+    fn reorder(&mut self, lo: usize, hi: usize) -> bool {
+        // which window positions are reachable from `lo`. Successors always come later in the order, so the search
+        // never leaves the window to the left, and nodes past `hi` can't lead back into it
+        let mut reached: Vec<bool, NODES> = Vec::new();
+        // can't fail: the window is at most NODES long
+        let _ = reached.resize(hi - lo + 1, false);
+        reached[0] = true;
+        let mut stack: Vec<usize, NODES> = Vec::new();
+        let _ = stack.push(lo);
+        while let Some(pos) = stack.pop() {
+            let node = self.order[pos];
+            for edge in self.graph.edges().iter().filter(|edge| edge.from == node) {
+                let succ = self.position(edge.to);
+                if succ <= hi && !reached[succ - lo] {
+                    if succ == hi {
+                        return false;
+                    }
+                    reached[succ - lo] = true;
+                    // can't fail: every position is pushed at most once
+                    let _ = stack.push(succ);
+                }
+            }
+        }
+
+        // stable partition of the window: unreached nodes first, then the reached ones
+        let mut window: Vec<N, NODES> = Vec::new();
+        for reached_pass in [false, true] {
+            for (offset, &node) in self.order[lo..=hi].iter().enumerate() {
+                if reached[offset] == reached_pass {
+                    // can't fail: the window is at most NODES long
+                    let _ = window.push(node);
+                }
+            }
+        }
+        for (offset, node) in window.into_iter().enumerate() {
+            self.order[lo + offset] = node;
+            // unwrap safety: every node in the order is indexed
+            let idx = self.nodes.index_of(node).unwrap();
+            self.positions[idx] = lo + offset;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Assert that the maintained order is a topological order of exactly the graph's nodes.
+    fn assert_valid<const EDGES: usize, const NODES: usize>(
+        graph: &OrderedGraph<usize, EDGES, NODES>,
+    ) {
+        let order = graph.order();
+        assert_eq!(Ok(order.len()), graph.graph().node_count());
+        for edge in graph.graph().edges() {
+            let from = order.iter().position(|&n| n == edge.from).unwrap();
+            let to = order.iter().position(|&n| n == edge.to).unwrap();
+            assert!(from < to, "{edge:?} violates {order:?}");
+        }
+    }
+
+    #[test]
+    fn insert_backwards() {
+        let mut graph = OrderedGraph::<usize, 16>::new();
+        for edge in [(0, 1), (2, 3), (4, 5)] {
+            graph.insert_edge(edge.into()).unwrap();
+        }
+        assert_eq!(&[0, 1, 2, 3, 4, 5], graph.order());
+        // only the part reachable from 3 moves behind 5
+        graph.insert_edge(Edge::new(5, 3)).unwrap();
+        assert_eq!(&[0, 1, 2, 4, 5, 3], graph.order());
+        graph.insert_edge(Edge::new(3, 0)).unwrap();
+        assert_eq!(&[2, 4, 5, 3, 0, 1], graph.order());
+        assert_valid(&graph);
+    }
+
+    #[test]
+    fn matches_edge_by_edge_sorting() {
+        // pseudo-random edges, keeping the ones that don't close a cycle
+        let mut graph = OrderedGraph::<usize, 64, 16>::new();
+        let mut state = 7u32;
+        for _ in 0..200 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let from = (state >> 16) as usize % 16;
+            let to = (state >> 8) as usize % 16;
+            let edge = Edge::new(from, to);
+            if graph.graph().remaining_capacity() == 0 {
+                break;
+            }
+            let expected = if from == to {
+                Err(Error::SelfLoop)
+            } else if graph.graph().would_create_cycle(edge) {
+                Err(Error::WouldCycle)
+            } else {
+                Ok(())
+            };
+            let before = graph.order().to_vec();
+            assert_eq!(expected, graph.insert_edge(edge));
+            if expected.is_err() {
+                assert_eq!(before, graph.order());
+            }
+            assert_valid(&graph);
+        }
+        assert_eq!(64, graph.graph().edge_count());
+    }
+
+    #[test]
+    fn from_graph() {
+        let edges = [(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2)];
+        let graph = Graph::<usize, 16>::from_array(edges);
+        let mut ordered = OrderedGraph::from_graph(graph.clone()).unwrap();
+        assert_eq!(graph.topo_sorted().unwrap().as_slice(), ordered.order());
+        ordered.add_node(9).unwrap();
+        ordered.insert_edge(Edge::new(0, 2)).unwrap();
+        ordered.insert_edge(Edge::new(9, 5)).unwrap();
+        assert_eq!(Err(Error::WouldCycle), ordered.insert_edge(Edge::new(1, 4)));
+        assert_valid(&ordered);
+
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 0)]);
+        assert!(matches!(
+            OrderedGraph::from_graph(graph),
+            Err(Error::Cycle(_))
+        ));
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let mut graph = OrderedGraph::<usize, 2, 3>::new();
+        graph.insert_edge(Edge::new(0, 1)).unwrap();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 4 }),
+            graph.insert_edge(Edge::new(2, 3))
+        );
+        graph.add_node(2).unwrap();
+        assert_eq!(Err(Error::OverCapacity { needed: 4 }), graph.add_node(3));
+        graph.insert_edge(Edge::new(2, 0)).unwrap();
+        assert_eq!(&[2, 0, 1], graph.order());
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.insert_edge(Edge::new(2, 1))
+        );
+        assert_valid(&graph);
+    }
+}
@@ -0,0 +1,147 @@
+//! Conversion from and to [`petgraph`] graphs, see [`Graph::from_petgraph`] and [`Graph::to_petgraph`].
+
+use core::hash::Hash;
+
+use petgraph::{
+    graph::{IndexType, NodeIndex},
+    Directed,
+};
+
+use crate::{Edge, Error, Graph};
+
+impl<const EDGES: usize, const NODES: usize> Graph<usize, EDGES, NODES> {
+    /// Copy the topology of a directed `petgraph::Graph`, e.g. to sort a subsystem on a `no_std` target.
+    ///
+    /// Node weights and edge weights are dropped: petgraph node `i` (see `NodeIndex::index`) becomes node `i`, and
+    /// the edges are copied in edge index order. Nodes without any edge are registered via [`Graph::add_node`], so
+    /// every petgraph node is part of the sort. Returns `Error::OverCapacity` if there are more than `EDGES` edges
+    /// or more than `NODES` nodes without edges.
+    ///
+    /// ```
+    /// use heapless_topo::Graph;
+    /// let mut input = petgraph::Graph::<&str, ()>::new();
+    /// let [a, b, c] = ["a", "b", "c"].map(|weight| input.add_node(weight));
+    /// input.extend_with_edges([(b, a), (c, b)]);
+    ///
+    /// let graph = Graph::<usize, 4>::from_petgraph(&input).ok().unwrap();
+    /// let sorted = graph.into_topo_sorted().ok().unwrap();
+    /// assert_eq!(&[c.index(), b.index(), a.index()], sorted.as_slice());
+    /// ```
+    pub fn from_petgraph<W, E, Ix: IndexType>(
+        graph: &petgraph::Graph<W, E, Directed, Ix>,
+    ) -> Result<Self, Error<usize, EDGES>> {
+        let needed = graph.edge_count();
+        if needed > EDGES {
+            return Err(Error::OverCapacity { needed });
+        }
+        let mut result = Self::new();
+        for node in graph.node_indices() {
+            if graph.neighbors_undirected(node).next().is_none() {
+                result.add_node(node.index())?;
+            }
+        }
+        for edge in graph.raw_edges() {
+            // can't fail: checked above
+            result.insert_edge(Edge::new(edge.source().index(), edge.target().index()))?;
+        }
+        Ok(result)
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Graph<N, EDGES, NODES> {
+    /// Copy the graph into a `petgraph::Graph`, e.g. to run petgraph's richer algorithms on it.
+    ///
+    /// Each node becomes a petgraph node weighted with its id, in order of first appearance (nodes added via
+    /// [`Graph::add_node`] first, then edge endpoints, see the crate docs on ordering), and the edges are copied in
+    /// order. Returns `Error::OverCapacity` if there are more than `NODES` distinct nodes.
+    ///
+    /// ```
+    /// use heapless_topo::Graph;
+    /// let graph = Graph::<char, 4>::from_array([('b', 'c'), ('a', 'b')]);
+    /// let output = graph.to_petgraph().ok().unwrap();
+    /// let sorted = petgraph::algo::toposort(&output, None).ok().unwrap();
+    /// assert_eq!(vec!['a', 'b', 'c'], sorted.iter().map(|&idx| output[idx]).collect::<Vec<_>>());
+    /// ```
+    pub fn to_petgraph(&self) -> Result<petgraph::Graph<N, ()>, Error<N, EDGES>> {
+        let nodes = self.node_set()?;
+        let mut graph = petgraph::Graph::with_capacity(nodes.len(), self.edges.len());
+        for &node in nodes.iter() {
+            graph.add_node(node);
+        }
+        // unwrap safety: every endpoint is in the node set
+        let index = |node| NodeIndex::new(nodes.index_of(node).unwrap());
+        for edge in &self.edges {
+            graph.add_edge(index(edge.from), index(edge.to), ());
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_petgraph() {
+        let mut input = petgraph::Graph::<u8, f32>::new();
+        let nodes = [10, 20, 30, 40].map(|weight| input.add_node(weight));
+        input.add_edge(nodes[2], nodes[0], 1.0);
+        input.add_edge(nodes[0], nodes[1], 2.0);
+        let graph = Graph::<usize, 4>::from_petgraph(&input).unwrap();
+        assert_eq!(&[Edge::new(2, 0), Edge::new(0, 1)], graph.edges());
+        // the isolated node is kept
+        assert_eq!(&[3, 2, 0, 1], graph.into_topo_sorted().unwrap().as_slice());
+
+        let graph = Graph::<usize, 2>::from_petgraph(&petgraph::Graph::<(), ()>::new()).unwrap();
+        assert!(graph.is_empty());
+    }
+
+    #[test]
+    fn petgraph_round_trip() {
+        let mut graph =
+            Graph::<usize, 8>::from_array([(50, 10), (40, 10), (40, 11), (30, 11), (20, 30)]);
+        graph.add_node(70).unwrap();
+        let output = graph.to_petgraph().unwrap();
+        assert_eq!(7, output.node_count());
+        assert_eq!(5, output.edge_count());
+
+        let dense = Graph::<usize, 8>::from_petgraph(&output).unwrap();
+        let translated = dense.edges().iter().map(|edge| {
+            Edge::new(
+                output[NodeIndex::new(edge.from)],
+                output[NodeIndex::new(edge.to)],
+            )
+        });
+        assert!(translated.eq(graph.edges().iter().copied()));
+        let sorted = dense.into_topo_sorted().unwrap();
+        let sorted = sorted.iter().map(|&idx| output[NodeIndex::new(idx)]);
+        assert!(sorted.eq(graph.clone().into_topo_sorted().unwrap()));
+
+        let graph = Graph::<usize, 4, 2>::from_array([(0, 1), (1, 2)]);
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.to_petgraph().map(|_| ())
+        );
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let mut input = petgraph::Graph::<(), ()>::new();
+        let [a, b, c] = [(); 3].map(|weight| input.add_node(weight));
+        input.extend_with_edges([(a, b), (b, c), (a, c)]);
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            Graph::<usize, 2>::from_petgraph(&input)
+        );
+
+        let mut input = petgraph::Graph::<(), ()>::new();
+        input.extend_with_edges([(0, 1)]);
+        for _ in 0..3 {
+            input.add_node(());
+        }
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            Graph::<usize, 2>::from_petgraph(&input)
+        );
+    }
+}
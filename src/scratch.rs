@@ -0,0 +1,105 @@
+//! Reusable temporary storage for sorting, see [`Scratch`].
+
+use core::hash::Hash;
+
+use heapless::Vec;
+
+use crate::{adjacency::Adjacency, Error, Graph};
+
+/// The temporary data structures of a sort (node index, adjacency list, in-degree counts and the order of
+/// dense indices), kept between sorts.
+///
+/// [`Graph::into_topo_sorted`] builds all of these on the stack for every call. On a device that re-sorts a graph
+/// every control cycle, a `Scratch` can live in a `static` or a long-lived task instead, so the stack usage of
+/// [`Graph::into_topo_sorted_with`] stays small and its memory use is fixed up front. The result is exactly the same
+/// as that of `into_topo_sorted`. Everything is overwritten on each call, so one `Scratch` can serve any number of
+/// graphs with the same capacities.
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct Scratch<N, const EDGES: usize, const NODES: usize = EDGES> {
+    adjacency: Adjacency<N, EDGES, NODES>,
+    order: Vec<usize, NODES>,
+}
+
+// by hand, see `NodeIndex`
+#[cfg(feature = "defmt-03")]
+impl<N, const EDGES: usize, const NODES: usize> defmt::Format for Scratch<N, EDGES, NODES> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Scratch {{ sorted: {} }}", self.order.len())
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Default
+    for Scratch<N, EDGES, NODES>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Scratch<N, EDGES, NODES> {
+    pub const fn new() -> Self {
+        Self {
+            adjacency: Adjacency::empty(),
+            order: Vec::new(),
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Graph<N, EDGES, NODES> {
+    /// compute topological sort, consuming self, using `scratch` for all temporary data. See [`Scratch`].
+    ///
+    /// ```
+    /// use heapless_topo::{Graph, Scratch};
+    /// let mut scratch = Scratch::<u8, 8>::new();
+    /// for _ in 0..3 {
+    ///     let graph = Graph::<u8, 8>::from_array([(1, 2), (0, 1)]);
+    ///     let sorted = graph.into_topo_sorted_with(&mut scratch).ok().unwrap();
+    ///     assert_eq!(&[0, 1, 2], sorted.as_slice());
+    /// }
+    /// ```
+    pub fn into_topo_sorted_with(
+        self,
+        scratch: &mut Scratch<N, EDGES, NODES>,
+    ) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        let Scratch { adjacency, order } = scratch;
+        adjacency.rebuild(&self.nodes, &self.edges)?;
+        adjacency.kahn_into(order);
+        self.collect_order(order, adjacency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_into_topo_sorted() {
+        let mut scratch = Scratch::<usize, 8>::new();
+        let graphs = [
+            Graph::<usize, 8>::from_array([(5, 0), (4, 0), (4, 1), (3, 1), (2, 3), (5, 2)]),
+            Graph::from_array([(0, 1), (1, 2), (2, 1)]),
+            Graph::from_array([(7, 6)]),
+            Graph::new(),
+        ];
+        for graph in graphs {
+            let expected = graph.clone().into_topo_sorted();
+            assert_eq!(expected, graph.into_topo_sorted_with(&mut scratch));
+        }
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let mut scratch = Scratch::<usize, 4, 2>::new();
+        let graph = Graph::<usize, 4, 2>::from_array([(0, 1), (1, 2)]);
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 3 }),
+            graph.into_topo_sorted_with(&mut scratch)
+        );
+        // still usable afterwards
+        let graph = Graph::<usize, 4, 2>::from_array([(1, 0)]);
+        assert_eq!(
+            Ok(&[1, 0][..]),
+            graph.into_topo_sorted_with(&mut scratch).as_deref()
+        );
+    }
+}
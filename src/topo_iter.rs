@@ -0,0 +1,140 @@
+//! Lazy topological sort, see [`Graph::topo_iter`].
+
+use core::hash::Hash;
+
+use heapless::Vec;
+
+use crate::{adjacency::Adjacency, find_cycle, Edge, Error, Graph};
+
+/// Iterator yielding the nodes of a graph in topological order, one at a time.
+///
+/// Each call to `next` picks a node without remaining incoming edges and removes its outgoing edges, so nodes can
+/// be processed as soon as they become ready. If the graph turns out to be cyclic, the iterator yields
+/// `Err(Error::Cycle)` after all sortable nodes and then stops; `Err(Error::OverCapacity)` is yielded up front if
+/// the graph doesn't fit the temporary data structures.
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+pub struct TopoIter<N, const EDGES: usize, const NODES: usize> {
+    state: State<N, EDGES, NODES>,
+}
+
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+enum State<N, const EDGES: usize, const NODES: usize> {
+    Sorting {
+        adjacency: Adjacency<N, EDGES, NODES>,
+        // kept around to report cycles
+        edges: Vec<Edge<N>, EDGES>,
+        // FIFO queue of starting nodes: everything past `head` is ready but not yet yielded
+        queue: Vec<usize, NODES>,
+        head: usize,
+    },
+    /// Nothing left to sort, only possibly an error to report
+    Finished(Option<Error<N, EDGES>>),
+}
+
+// by hand, see `NodeIndex`
+#[cfg(feature = "defmt-03")]
+impl<N: Copy + Eq + Hash + defmt::Format, const EDGES: usize, const NODES: usize> defmt::Format
+    for TopoIter<N, EDGES, NODES>
+{
+    fn format(&self, f: defmt::Formatter) {
+        match &self.state {
+            State::Sorting {
+                adjacency, head, ..
+            } => defmt::write!(
+                f,
+                "TopoIter {{ yielded: {}, nodes: {} }}",
+                head,
+                adjacency.len()
+            ),
+            State::Finished(err) => defmt::write!(f, "TopoIter {{ finished: {} }}", err),
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Graph<N, EDGES, NODES> {
+    /// Lazily compute the topological sort, consuming self. See [`TopoIter`].
+    pub fn topo_iter(self) -> TopoIter<N, EDGES, NODES> {
+        let state = match Adjacency::new(&self.nodes, &self.edges) {
+            Ok(adjacency) => State::Sorting {
+                queue: adjacency.starting_nodes(),
+                adjacency,
+                edges: self.edges,
+                head: 0,
+            },
+            Err(err) => State::Finished(Some(err)),
+        };
+        TopoIter { state }
+    }
+}
+
+impl<N: Copy + Eq + Hash, const EDGES: usize, const NODES: usize> Iterator
+    for TopoIter<N, EDGES, NODES>
+{
+    type Item = Result<N, Error<N, EDGES>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.state {
+            State::Sorting {
+                adjacency,
+                edges,
+                queue,
+                head,
+            } => {
+                if let Some(&idx) = queue.get(*head) {
+                    *head += 1;
+                    // can't fail: every node is pushed at most once
+                    adjacency.remove_outgoing(idx, |succ| {
+                        let _ = queue.push(succ);
+                    });
+                    return Some(Ok(adjacency.node(idx)));
+                }
+                let err = if *head == adjacency.len() {
+                    None
+                } else {
+                    let residual = edges.iter().filter(|edge| !adjacency.is_sorted(edge.from));
+                    Some(match find_cycle(residual) {
+                        Ok(cycle) => Error::Cycle(cycle),
+                        Err(err) => err,
+                    })
+                };
+                self.state = State::Finished(None);
+                err.map(Err)
+            }
+            State::Finished(err) => err.take().map(Err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ok() {
+        let graph = Graph::<usize, 8>::from_array([(1, 2), (2, 3), (3, 4), (4, 5), (3, 5), (1, 5)]);
+        let mut iter = graph.topo_iter();
+        for expected in 1..=5 {
+            assert_eq!(Some(Ok(expected)), iter.next());
+        }
+        assert_eq!(None, iter.next());
+        assert_eq!(None, iter.next());
+    }
+
+    #[test]
+    fn err_cycle() {
+        let graph = Graph::<usize, 8>::from_array([(0, 1), (1, 2), (2, 1)]);
+        let mut iter = graph.topo_iter();
+        assert_eq!(Some(Ok(0)), iter.next());
+        let expected = [2, 1].as_slice().try_into().unwrap();
+        assert_eq!(Some(Err(Error::Cycle(expected))), iter.next());
+        assert_eq!(None, iter.next());
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let graph = Graph::<usize, 2>::from_array([(0, 1), (1, 2)]);
+        let mut iter = graph.topo_iter();
+        assert_eq!(Some(Err(Error::OverCapacity { needed: 3 })), iter.next());
+        assert_eq!(None, iter.next());
+    }
+}
@@ -0,0 +1,179 @@
+//! Graph with weighted edges, see [`WeightedGraph`].
+
+use core::{hash::Hash, ops::Add};
+
+use heapless::Vec;
+
+use crate::{Edge, Error, Graph};
+
+/// A [`Graph`] whose edges carry a weight, e.g. a latency.
+///
+/// Sorting only looks at the topology, the weights just ride along and are available for computations like
+/// [`WeightedGraph::critical_path_weight`]. Read-only topology queries are available via [`WeightedGraph::graph`].
+#[cfg_attr(any(test, feature = "std"), derive(Debug))]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(PartialEq, Eq, Clone)]
+pub struct WeightedGraph<N, W, const EDGES: usize, const NODES: usize = EDGES> {
+    graph: Graph<N, EDGES, NODES>,
+    // parallel to `graph.edges`
+    weights: Vec<W, EDGES>,
+}
+
+impl<N, W, const EDGES: usize, const NODES: usize> Default for WeightedGraph<N, W, EDGES, NODES> {
+    fn default() -> Self {
+        Self {
+            graph: Graph::default(),
+            weights: Vec::new(),
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash, W: Copy, const EDGES: usize, const NODES: usize>
+    WeightedGraph<N, W, EDGES, NODES>
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert an edge with its weight. No duplicate check is performed.
+    /// Returns `Error::OverCapacity` if full.
+    pub fn insert_edge(&mut self, edge: Edge<N>, weight: W) -> Result<(), Error<N, EDGES>> {
+        self.graph.insert_edge(edge)?;
+        // can't fail: `weights` has room for as many entries as `graph.edges`
+        self.weights
+            .push(weight)
+            .map_err(|_| Error::OverCapacity { needed: EDGES + 1 })
+    }
+
+    /// Register a node, see [`Graph::add_node`].
+    pub fn add_node(&mut self, node: N) -> Result<(), Error<N, EDGES>> {
+        self.graph.add_node(node)
+    }
+
+    /// The topology, without weights.
+    pub fn graph(&self) -> &Graph<N, EDGES, NODES> {
+        &self.graph
+    }
+
+    /// All edges with their weights, in insertion order.
+    pub fn edges(&self) -> impl Iterator<Item = (Edge<N>, W)> + '_ {
+        self.graph
+            .edges
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+    }
+
+    /// compute topological sort, consuming self. See [`Graph::into_topo_sorted`].
+    pub fn into_topo_sorted(self) -> Result<Vec<N, NODES>, Error<N, EDGES>> {
+        self.graph.into_topo_sorted()
+    }
+
+    /// Total weight of the heaviest path through the graph, consuming self.
+    ///
+    /// This is the weighted counterpart of [`Graph::critical_path_len`]: with latencies as weights, it's the
+    /// minimum completion time. Paths may start and end at any node; the empty path has weight `W::default()`,
+    /// which is also the result for graphs without edges. Returns `Error::Cycle` for cyclic graphs.
+    ///
+    /// ```rust
+    /// # use heapless_topo::{Edge, WeightedGraph};
+    /// let mut graph = WeightedGraph::<u8, u32, 4>::new();
+    /// graph.insert_edge(Edge::new(0, 1), 10).ok().unwrap();
+    /// graph.insert_edge(Edge::new(1, 2), 10).ok().unwrap();
+    /// graph.insert_edge(Edge::new(0, 2), 30).ok().unwrap();
+    /// assert!(graph.critical_path_weight() == Ok(30));
+    /// ```
+    pub fn critical_path_weight(self) -> Result<W, Error<N, EDGES>>
+    where
+        W: Add<Output = W> + Ord + Default,
+    {
+        let (order, adjacency) = self.graph.kahn()?;
+        self.graph.check_complete(&order, &adjacency)?;
+
+        // dense index -> position in the topological order
+        let mut positions: Vec<usize, NODES> = Vec::new();
+        // can't fail: one entry per node
+        let _ = positions.resize(adjacency.len(), 0);
+        for (position, &idx) in order.iter().enumerate() {
+            positions[idx] = position;
+        }
+        // relaxing edges in topological order of their source sees every node's final weight before using it
+        let edges = &self.graph.edges;
+        // unwrap safety: all endpoints are indexed
+        let index = |node| adjacency.index(node).unwrap();
+        let mut edge_order: Vec<usize, EDGES> = (0..edges.len()).collect();
+        edge_order.sort_unstable_by_key(|&pos| positions[index(edges[pos].from)]);
+
+        let mut weights: Vec<W, NODES> = Vec::new();
+        let _ = weights.resize(adjacency.len(), W::default());
+        for pos in edge_order {
+            let (from, to) = (index(edges[pos].from), index(edges[pos].to));
+            let weight = weights[from] + self.weights[pos];
+            if weight > weights[to] {
+                weights[to] = weight;
+            }
+        }
+        Ok(weights.iter().copied().max().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_and_edges() {
+        let mut graph = WeightedGraph::<usize, u32, 8>::new();
+        graph.insert_edge(Edge::new(1, 2), 5).unwrap();
+        graph.insert_edge(Edge::new(0, 1), 7).unwrap();
+        graph.add_node(3).unwrap();
+        let edges: Vec<(Edge<usize>, u32), 8> = graph.edges().collect();
+        assert_eq!(
+            &[(Edge::new(1, 2), 5), (Edge::new(0, 1), 7)],
+            edges.as_slice()
+        );
+        assert_eq!(2, graph.graph().edge_count());
+        assert_eq!(&[3, 0, 1, 2], graph.into_topo_sorted().unwrap().as_slice());
+    }
+
+    #[test]
+    fn critical_path_weight() {
+        let mut graph = WeightedGraph::<usize, u32, 8>::new();
+        // the longest path by edges (0 -> 1 -> 2 -> 4) isn't the heaviest one (0 -> 3 -> 4)
+        for (from, to, weight) in [
+            (2, 4, 1),
+            (0, 1, 1),
+            (1, 2, 1),
+            (0, 3, 5),
+            (3, 4, 5),
+            (5, 3, 1),
+        ] {
+            graph.insert_edge(Edge::new(from, to), weight).unwrap();
+        }
+        assert_eq!(Ok(10), graph.critical_path_weight());
+
+        let mut graph = WeightedGraph::<usize, i32, 8>::new();
+        assert_eq!(Ok(0), graph.clone().critical_path_weight());
+        graph.insert_edge(Edge::new(0, 1), -3).unwrap();
+        assert_eq!(Ok(0), graph.critical_path_weight());
+    }
+
+    #[test]
+    fn err_cycle() {
+        let mut graph = WeightedGraph::<usize, u32, 8>::new();
+        graph.insert_edge(Edge::new(0, 1), 1).unwrap();
+        graph.insert_edge(Edge::new(1, 0), 1).unwrap();
+        assert!(matches!(graph.critical_path_weight(), Err(Error::Cycle(_))));
+    }
+
+    #[test]
+    fn err_over_capacity() {
+        let mut graph = WeightedGraph::<usize, u32, 1>::new();
+        graph.insert_edge(Edge::new(0, 1), 1).unwrap();
+        assert_eq!(
+            Err(Error::OverCapacity { needed: 2 }),
+            graph.insert_edge(Edge::new(1, 2), 1)
+        );
+        assert_eq!(1, graph.edges().count());
+    }
+}